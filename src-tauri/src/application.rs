@@ -1,4 +1,18 @@
+pub mod account_service;
 pub mod commands;
+pub mod dedupe_service;
+pub mod deploy_service;
+pub mod engine_service;
+pub mod export_service;
+pub mod extract_service;
 pub mod history_service;
+pub mod janitor_service;
+pub mod library_service;
 pub mod queue_manager;
+pub mod settings_service;
+pub mod startup_service;
+pub mod trash_service;
+pub mod update_service;
+pub mod verify_service;
+pub mod version_service;
 
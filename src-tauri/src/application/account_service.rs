@@ -0,0 +1,89 @@
+use crate::application::settings_service::current_settings;
+use crate::domain::steamcmd_script::{check_account_name, check_password};
+use crate::domain::types::{LoginMode, SteamGuardState};
+use crate::infrastructure::credential_store::{CredentialStore, SteamCredentials};
+use tauri::{AppHandle, Emitter, Manager};
+
+fn credential_store(app: &AppHandle) -> Result<CredentialStore, String> {
+    CredentialStore::new(&app.config().identifier)
+}
+
+/// The saved account name, without the password.
+pub fn get_account(app: &AppHandle) -> Result<Option<String>, String> {
+    Ok(credential_store(app)?.load()?.map(|c| c.username))
+}
+
+/// Saves the account to log in with. The password can be left out when steamcmd
+/// already has a cached login for it; a new password replaces the saved one, and
+/// leaving it out keeps the saved one for the same account.
+pub fn save_account(
+    app: &AppHandle,
+    username: &str,
+    password: Option<String>,
+) -> Result<(), String> {
+    let username = username.trim();
+    check_account_name(username)?;
+    if let Some(password) = &password {
+        check_password(password)?;
+    }
+    let store = credential_store(app)?;
+    let password = match password {
+        Some(password) => Some(password),
+        None => store
+            .load()
+            .ok()
+            .flatten()
+            .filter(|c| c.username == username)
+            .and_then(|c| c.password),
+    };
+    store.save(&SteamCredentials {
+        username: username.to_string(),
+        password,
+    })
+}
+
+pub fn remove_account(app: &AppHandle) -> Result<(), String> {
+    credential_store(app)?.clear()
+}
+
+/// The saved account, if there is one that can be read.
+pub fn account_credentials(app: &AppHandle) -> Option<SteamCredentials> {
+    match credential_store(app).and_then(|store| store.load()) {
+        Ok(credentials) => credentials,
+        Err(e) => {
+            let _ = app.emit(
+                "terminal-output",
+                format!("Could not read the saved Steam account: {}", e),
+            );
+            None
+        }
+    }
+}
+
+/// The account to start a download with, or `None` for an anonymous login.
+pub fn login_credentials(app: &AppHandle) -> Result<Option<SteamCredentials>, String> {
+    match current_settings(app).login {
+        LoginMode::Anonymous => Ok(None),
+        LoginMode::Account => account_credentials(app)
+            .map(Some)
+            .ok_or_else(|| "No Steam account is saved".to_string()),
+    }
+}
+
+/// Passes a Steam Guard code to the steamcmd login waiting for one.
+pub fn submit_guard_code(app: &AppHandle, code: &str) -> Result<(), String> {
+    let code = code.trim();
+    if code.is_empty() || !code.chars().all(|c| c.is_ascii_alphanumeric()) {
+        return Err("Invalid Steam Guard code".to_string());
+    }
+    let state = app.state::<SteamGuardState>();
+    let sender = state
+        .0
+        .lock()
+        .map_err(|_| "Failed to lock Steam Guard state")?
+        .take()
+        .ok_or("steamcmd is not waiting for a Steam Guard code")?;
+    sender
+        .send(code.to_ascii_uppercase())
+        .map_err(|_| "The login is no longer waiting for a code".to_string())
+}
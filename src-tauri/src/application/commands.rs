@@ -1,8 +1,17 @@
 use tauri::{command, State, AppHandle};
-use crate::domain::types::{DownloadItem, QueueState, Status, HistoryItem, HistoryState};
+use crate::domain::types::{
+    ArchiveFormat, DedupeReport, Deployment, DownloadItem, EngineState, ExtractMode, QueueState, Status, HistoryItem, HistoryState, LibraryReport, Settings, SettingsState, StartupReport,
+    TempFolder, TrashEntry, TrashState, VersionSnapshot,
+};
 use crate::domain::parser::parse_workshop_html;
-use crate::infrastructure::file_manager;
-use crate::application::history_service;
+use crate::domain::steamcmd_script::SteamTarget;
+use crate::infrastructure::workshop_api::fetch_details;
+use crate::application::{
+    account_service, dedupe_service, deploy_service, engine_service, export_service, extract_service, history_service, janitor_service, library_service, settings_service,
+    startup_service, trash_service, verify_service, version_service,
+};
+use crate::domain::gma::GmaHeader;
+use crate::domain::manifest::VerifyReport;
 use std::sync::Mutex;
 
 pub struct AppState(pub Mutex<QueueState>);
@@ -18,17 +27,202 @@ pub async fn clear_history(app: AppHandle, state: State<'_, HistoryState>) -> Re
 }
 
 #[command]
-pub async fn remove_history_item(app: AppHandle, state: State<'_, HistoryState>, id: String) -> Result<(), String> {
-    history_service::remove_item(&app, &state, &id)
+pub async fn remove_history_item(
+    app: AppHandle,
+    state: State<'_, HistoryState>,
+    trash: State<'_, TrashState>,
+    id: String,
+    keep_files: Option<bool>,
+) -> Result<(), String> {
+    history_service::remove_item(&app, &state, &trash, &id, keep_files.unwrap_or(false))
+}
+
+#[command]
+pub async fn deploy_item(app: AppHandle, state: State<'_, HistoryState>, id: String) -> Result<Deployment, String> {
+    deploy_service::deploy_item(&app, &state, &id)
+}
+
+#[command]
+pub async fn undeploy_item(app: AppHandle, state: State<'_, HistoryState>, id: String) -> Result<(), String> {
+    deploy_service::undeploy_item(&app, &state, &id)
+}
+
+#[command]
+pub async fn verify_item(app: AppHandle, state: State<'_, HistoryState>, id: String) -> Result<VerifyReport, String> {
+    verify_service::verify_item(&app, &state, &id)
+}
+
+#[command]
+pub async fn verify_library(app: AppHandle, state: State<'_, HistoryState>) -> Result<(), String> {
+    verify_service::verify_library(&app, &state)
+}
+
+#[command]
+pub async fn list_versions(state: State<'_, HistoryState>, id: String) -> Result<Vec<VersionSnapshot>, String> {
+    version_service::list_versions(&state, &id)
+}
+
+#[command]
+pub async fn rollback_item(
+    app: AppHandle,
+    state: State<'_, HistoryState>,
+    id: String,
+    version_id: String,
+) -> Result<HistoryItem, String> {
+    version_service::rollback_item(&app, &state, &id, &version_id)
+}
+
+#[command]
+pub async fn set_keep_versions(
+    app: AppHandle,
+    state: State<'_, HistoryState>,
+    id: String,
+    keep: Option<u32>,
+) -> Result<(), String> {
+    version_service::set_keep_versions(&app, &state, &id, keep)
+}
+
+#[command]
+pub async fn dedupe_library(app: AppHandle, state: State<'_, HistoryState>) -> Result<DedupeReport, String> {
+    dedupe_service::dedupe_library(&app, &state)
+}
+
+#[command]
+pub async fn export_items(
+    app: AppHandle,
+    state: State<'_, HistoryState>,
+    ids: Vec<String>,
+    output_path: String,
+    format: ArchiveFormat,
+) -> Result<(), String> {
+    export_service::export_items(&app, &state, &ids, &output_path, format)
+}
+
+#[command]
+pub async fn get_trash(state: State<'_, TrashState>) -> Result<Vec<TrashEntry>, String> {
+    trash_service::get_all_trash(&state)
+}
+
+#[command]
+pub async fn restore_history_item(
+    app: AppHandle,
+    history: State<'_, HistoryState>,
+    trash: State<'_, TrashState>,
+    id: String,
+) -> Result<HistoryItem, String> {
+    trash_service::restore_item(&app, &history, &trash, &id)
+}
+
+#[command]
+pub async fn empty_trash(app: AppHandle, state: State<'_, TrashState>) -> Result<(), String> {
+    trash_service::empty_trash(&app, &state)
+}
+
+#[command]
+pub async fn get_settings(state: State<'_, SettingsState>) -> Result<Settings, String> {
+    settings_service::get_settings(&state)
+}
+
+#[command]
+pub async fn update_settings(app: AppHandle, state: State<'_, SettingsState>, settings: Settings) -> Result<(), String> {
+    settings_service::update_settings(&app, &state, settings)
+}
+
+#[command]
+pub async fn detect_steamcmd() -> Result<Vec<String>, String> {
+    Ok(settings_service::detect_steamcmd())
+}
+
+#[command]
+pub async fn check_steamcmd(app: AppHandle, path: String) -> Result<(), String> {
+    settings_service::check_steamcmd(&app, &path)
+}
+
+#[command]
+pub async fn prepare_engine(app: AppHandle) -> Result<(), String> {
+    engine_service::prepare_engine(&app)
+}
+
+#[command]
+pub fn get_engine_state(app: AppHandle) -> EngineState {
+    engine_service::get_engine_state(&app)
+}
+
+#[command]
+pub async fn get_account(app: AppHandle) -> Result<Option<String>, String> {
+    account_service::get_account(&app)
+}
+
+#[command]
+pub async fn save_account(app: AppHandle, username: String, password: Option<String>) -> Result<(), String> {
+    account_service::save_account(&app, &username, password)
+}
+
+#[command]
+pub async fn remove_account(app: AppHandle) -> Result<(), String> {
+    account_service::remove_account(&app)
+}
+
+#[command]
+pub fn submit_guard_code(app: AppHandle, code: String) -> Result<(), String> {
+    account_service::submit_guard_code(&app, &code)
+}
+
+#[command]
+pub async fn reconcile_library(app: AppHandle, state: State<'_, HistoryState>) -> Result<LibraryReport, String> {
+    library_service::reconcile_library(&app, &state)
+}
+
+#[command]
+pub async fn adopt_orphan(
+    app: AppHandle,
+    state: State<'_, HistoryState>,
+    path: String,
+    steam_id: Option<String>,
+) -> Result<HistoryItem, String> {
+    library_service::adopt_orphan(&app, &state, &path, steam_id)
+}
+
+#[command]
+pub async fn delete_orphan(app: AppHandle, state: State<'_, HistoryState>, path: String) -> Result<(), String> {
+    library_service::delete_orphan(&app, &state, &path)
+}
+
+#[command]
+pub async fn scan_temp_folders(app: AppHandle) -> Result<Vec<TempFolder>, String> {
+    janitor_service::scan_temp_folders(&app)
+}
+
+#[command]
+pub fn get_startup_report(app: AppHandle) -> StartupReport {
+    startup_service::get_startup_report(&app)
+}
+
+#[command]
+pub async fn clean_temp_folders(app: AppHandle, paths: Option<Vec<String>>) -> Result<u64, String> {
+    janitor_service::clean_temp_folders(&app, paths)
+}
+
+#[command]
+pub async fn list_gma(app: AppHandle, path: String) -> Result<GmaHeader, String> {
+    extract_service::list_gma(&app, &path)
 }
 
 #[command]
 pub async fn add_download(
+    app: AppHandle,
     state: State<'_, AppState>,
     steam_id: String,
     name: String,
+    destination: Option<String>,
+    flatten: Option<bool>,
+    extract: Option<ExtractMode>,
 ) -> Result<String, String> {
-    let (final_id, final_name) = if steam_id.contains("steamcommunity.com") {
+    if let Some(dest) = destination.as_deref() {
+        library_service::path_policy(&app)?.check_target(std::path::Path::new(dest))?;
+    }
+
+    let (target, final_name, app_name) = if steam_id.contains("steamcommunity.com") {
         let output = std::process::Command::new("curl")
             .args(["-s", "-L", "-A", "Mozilla/5.0", &steam_id])
             .output()
@@ -43,10 +237,20 @@ pub async fn add_download(
             .ok_or("Invalid URL parameters")?;
             
         let meta = parse_workshop_html(&html, file_id)?;
-        
-        (format!("{}:{}", meta.app_id, meta.file_id), meta.title)
+        let target = SteamTarget::Workshop {
+            app_id: meta.app_id.parse()?,
+            file_id: meta.file_id.parse()?,
+        };
+
+        (target, meta.title, meta.app_name)
     } else {
-        (steam_id, name)
+        let target: SteamTarget = steam_id.parse()?;
+        (target, name, None)
+    };
+
+    let details = match target {
+        SteamTarget::Workshop { file_id, .. } => fetch_details(file_id).ok(),
+        SteamTarget::App(_) => None,
     };
 
     let id = uuid::Uuid::new_v4().to_string();
@@ -54,7 +258,7 @@ pub async fn add_download(
     
     let item = DownloadItem {
         id: id.clone(),
-        steam_id: final_id,
+        steam_id: target.to_string(),
         name: final_name,
         status: Status::Pending,
         install_path: None,
@@ -62,6 +266,12 @@ pub async fn add_download(
             .duration_since(std::time::UNIX_EPOCH)
             .map_err(|e| e.to_string())?
             .as_secs(),
+        app_name,
+        destination,
+        time_updated: details.as_ref().and_then(|d| d.time_updated),
+        file_size: details.as_ref().and_then(|d| d.file_size),
+        flatten,
+        extract,
     };
     
     q.items.push(item);
@@ -75,20 +285,24 @@ pub fn get_queue(state: State<'_, AppState>) -> Result<Vec<DownloadItem>, String
 }
 
 #[command]
-pub fn open_folder(_state: State<'_, AppState>, path: String) -> Result<(), String> {
-    file_manager::open_path(&path).map_err(|e| e.to_string())
+pub fn open_folder(app: AppHandle, path: String) -> Result<(), String> {
+    library_service::open_folder(&app, &path)
 }
 
 #[command]
-pub fn check_path_exists(path: String) -> bool {
-    file_manager::check_path_exists(&path)
+pub fn check_path_exists(app: AppHandle, path: String) -> Result<bool, String> {
+    library_service::path_exists(&app, &path)
 }
 
 #[command]
 pub async fn retry_download(
+    app: AppHandle,
     state: State<'_, AppState>,
     steam_id: String,
     name: String,
+    destination: Option<String>,
+    flatten: Option<bool>,
+    extract: Option<ExtractMode>,
 ) -> Result<String, String> {
-    add_download(state, steam_id, name).await
+    add_download(app, state, steam_id, name, destination, flatten, extract).await
 }
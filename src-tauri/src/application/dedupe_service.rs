@@ -0,0 +1,128 @@
+use crate::application::library_service::library_roots;
+use crate::application::version_service::versions_dir;
+use crate::domain::types::{DedupeReport, HistoryState, Outcome};
+use crate::infrastructure::content_store::{unshare_dir, ContentStore};
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use tauri::{AppHandle, Emitter};
+
+/// Kept inside each library root so installs and store share a filesystem.
+const STORE_DIR_NAME: &str = ".steamdl-store";
+
+/// Links a freshly installed item against the content store.
+pub fn dedupe_install(app: &AppHandle, install_dir: &Path) -> Result<DedupeReport, String> {
+    let mut store = ContentStore::new(store_root(app, install_dir)?);
+    let mut report = DedupeReport::default();
+    dedupe_into(&mut store, install_dir, &mut report)?;
+    Ok(report)
+}
+
+/// Converts every completed install to use the content store. Reports progress
+/// through `dedupe-progress`.
+pub fn dedupe_library(app: &AppHandle, state: &HistoryState) -> Result<DedupeReport, String> {
+    let dirs: Vec<(String, PathBuf)> = state
+        .0
+        .lock()
+        .map_err(|_| "Failed to lock mutex")?
+        .iter()
+        .filter(|h| matches!(h.outcome, Outcome::Completed) && !h.install_path.is_empty())
+        .map(|h| (h.name.clone(), PathBuf::from(&h.install_path)))
+        .filter(|(_, dir)| dir.is_dir())
+        .collect();
+
+    let mut stores: HashMap<PathBuf, ContentStore> = HashMap::new();
+    let mut report = DedupeReport::default();
+    let total = dirs.len();
+    for (index, (name, dir)) in dirs.iter().enumerate() {
+        let _ = app.emit(
+            "dedupe-progress",
+            serde_json::json!({ "name": name, "current": index + 1, "total": total }),
+        );
+        let root = store_root(app, dir)?;
+        let store = stores
+            .entry(root.clone())
+            .or_insert_with(|| ContentStore::new(root));
+        if let Err(e) = dedupe_into(store, dir, &mut report) {
+            let _ = app.emit(
+                "terminal-output",
+                format!("Dedupe failed for {}: {}", name, e),
+            );
+        }
+        // Versions linked by earlier runs, when they were still deduplicated too.
+        unshare_version(app, &versions_dir(dir));
+    }
+    for store in stores.values() {
+        report.bytes_freed += store.collect_garbage().map_err(|e| e.to_string())?;
+    }
+    Ok(report)
+}
+
+// Kept versions stay out of the store; a write to an install must not reach them.
+fn dedupe_into(
+    store: &mut ContentStore,
+    install_dir: &Path,
+    report: &mut DedupeReport,
+) -> Result<(), String> {
+    store
+        .dedupe_dir(install_dir, report)
+        .map_err(|e| e.to_string())
+}
+
+/// Gives a version set aside from a deduplicated install its own copies of the shared
+/// files.
+pub fn unshare_version(app: &AppHandle, version_dir: &Path) {
+    if !version_dir.is_dir() {
+        return;
+    }
+    if let Err(e) = unshare_dir(version_dir) {
+        let _ = app.emit(
+            "terminal-output",
+            format!(
+                "Failed to separate {} from the store: {}",
+                version_dir.display(),
+                e
+            ),
+        );
+    }
+}
+
+/// Frees the store blobs that deleting the given installs (or their versions) left
+/// unused. Deleted files only give back their space once this has run.
+pub fn collect_garbage_for<'a>(app: &AppHandle, install_dirs: impl IntoIterator<Item = &'a Path>) {
+    let roots: HashSet<PathBuf> = install_dirs
+        .into_iter()
+        .filter(|dir| !dir.as_os_str().is_empty())
+        .filter_map(|dir| store_root(app, dir).ok())
+        .collect();
+    for root in roots {
+        if !root.is_dir() {
+            continue;
+        }
+        match ContentStore::new(root).collect_garbage() {
+            Ok(0) => {}
+            Ok(freed) => {
+                let _ = app.emit(
+                    "terminal-output",
+                    format!("Freed {} bytes of unused deduplicated files", freed),
+                );
+            }
+            Err(e) => {
+                let _ = app.emit(
+                    "terminal-output",
+                    format!("Failed to clean up the content store: {}", e),
+                );
+            }
+        }
+    }
+}
+
+// The store of the library root holding the install; installs placed elsewhere get
+// one beside them.
+fn store_root(app: &AppHandle, install_dir: &Path) -> Result<PathBuf, String> {
+    let root = library_roots(app)?
+        .into_iter()
+        .find(|root| install_dir.starts_with(root))
+        .or_else(|| install_dir.parent().map(Path::to_path_buf))
+        .ok_or("Invalid install path")?;
+    Ok(root.join(STORE_DIR_NAME))
+}
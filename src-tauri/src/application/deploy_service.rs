@@ -0,0 +1,210 @@
+use crate::application::settings_service::current_settings;
+use crate::domain::steamcmd_script::SteamTarget;
+use crate::domain::types::{DeployMode, DeployTarget, Deployment, HistoryItem, HistoryState};
+use crate::infrastructure::deployer;
+use crate::infrastructure::path_policy::PathPolicy;
+use crate::infrastructure::persistence::save_history;
+use std::fs;
+use std::path::{Path, PathBuf};
+use tauri::{AppHandle, Emitter};
+
+pub fn deploy_item(app: &AppHandle, state: &HistoryState, id: &str) -> Result<Deployment, String> {
+    let item = find_item(state, id)?.ok_or("History item not found")?;
+    let target = deploy_target_for(app, &item.steam_id)
+        .ok_or_else(|| format!("No deploy target configured for {}", item.steam_id))?;
+    let deployment = deploy_to(app, &item, &target)?;
+    record_deployment(app, state, id, deployment)
+}
+
+pub fn undeploy_item(app: &AppHandle, state: &HistoryState, id: &str) -> Result<(), String> {
+    let mut history = state.0.lock().map_err(|_| "Failed to lock mutex")?;
+    let item = history
+        .iter_mut()
+        .find(|h| h.id == id)
+        .ok_or("History item not found")?;
+    let result = undeploy_all(app, item);
+    save_history(app, &history)?;
+    result
+}
+
+/// Deploys a freshly completed item if its app profile asks for it.
+pub fn auto_deploy(app: &AppHandle, state: &HistoryState, id: &str) -> Result<(), String> {
+    let Some(item) = find_item(state, id)? else {
+        return Ok(());
+    };
+    match deploy_target_for(app, &item.steam_id) {
+        // An update carries the earlier deployments over and refreshes them.
+        Some(target) if target.auto && !is_deployed_to(&item, &target) => {
+            let deployment = deploy_to(app, &item, &target)?;
+            record_deployment(app, state, id, deployment).map(|_| ())
+        }
+        _ => Ok(()),
+    }
+}
+
+/// Deploys `id` again over its copy and hardlink deployments after its install folder
+/// was replaced, since those still hold the earlier files. Symlinks follow on their own.
+pub fn refresh_deployments(app: &AppHandle, state: &HistoryState, id: &str) -> Result<(), String> {
+    let Some(item) = find_item(state, id)? else {
+        return Ok(());
+    };
+    let mut errors = Vec::new();
+    for deployment in item
+        .deployments
+        .iter()
+        .filter(|d| d.mode != DeployMode::Symlink)
+    {
+        let Some(dir) = Path::new(&deployment.path).parent() else {
+            continue;
+        };
+        let target = DeployTarget {
+            dir: dir.to_string_lossy().to_string(),
+            mode: deployment.mode,
+            auto: false,
+        };
+        let result =
+            deploy_to(app, &item, &target).and_then(|d| record_deployment(app, state, id, d));
+        if let Err(e) = result {
+            errors.push(format!("{}: {}", deployment.path, e));
+        }
+    }
+    if errors.is_empty() {
+        Ok(())
+    } else {
+        Err(errors.join("; "))
+    }
+}
+
+fn is_deployed_to(item: &HistoryItem, target: &DeployTarget) -> bool {
+    item.deployments
+        .iter()
+        .any(|d| Path::new(&d.path).parent() == Some(Path::new(&target.dir)))
+}
+
+// Deploys copy whole folders, so they work on a snapshot of the item instead of
+// holding the history lock for the duration.
+fn find_item(state: &HistoryState, id: &str) -> Result<Option<HistoryItem>, String> {
+    let history = state.0.lock().map_err(|_| "Failed to lock mutex")?;
+    Ok(history.iter().find(|h| h.id == id).cloned())
+}
+
+fn record_deployment(
+    app: &AppHandle,
+    state: &HistoryState,
+    id: &str,
+    deployment: Deployment,
+) -> Result<Deployment, String> {
+    let mut history = state.0.lock().map_err(|_| "Failed to lock mutex")?;
+    let Some(item) = history.iter_mut().find(|h| h.id == id) else {
+        drop(history);
+        let _ = deployer::undeploy(Path::new(&deployment.path), deployment.mode);
+        return Err("History item was removed during the deploy".to_string());
+    };
+    item.deployments.retain(|d| d.path != deployment.path);
+    item.deployments.push(deployment.clone());
+    save_history(app, &history)?;
+    Ok(deployment)
+}
+
+/// Removes every tracked deployment of `item`. Deployments that could not be removed
+/// stay tracked so a later attempt can retry them.
+pub fn undeploy_all(app: &AppHandle, item: &mut HistoryItem) -> Result<(), String> {
+    let policy = deploy_policy(app);
+    let mut errors = Vec::new();
+    item.deployments
+        .retain(|d| match remove_deployment(&policy, d) {
+            Ok(()) => false,
+            Err(e) => {
+                errors.push(e);
+                true
+            }
+        });
+    if errors.is_empty() {
+        Ok(())
+    } else {
+        Err(errors.join("; "))
+    }
+}
+
+fn deploy_to(
+    app: &AppHandle,
+    item: &HistoryItem,
+    target: &DeployTarget,
+) -> Result<Deployment, String> {
+    let src = Path::new(&item.install_path);
+    if item.install_path.is_empty() || !src.is_dir() {
+        return Err("Install folder is missing".to_string());
+    }
+    let folder_name = src.file_name().ok_or("Invalid install path")?;
+    let dst = Path::new(&target.dir).join(folder_name);
+    let dst_str = dst.to_string_lossy().to_string();
+
+    // Redeploying replaces our own earlier deployment at the same location.
+    if let Some(existing) = item.deployments.iter().find(|d| d.path == dst_str) {
+        remove_deployment(&deploy_policy(app), existing)?;
+    }
+    if fs::symlink_metadata(&dst).is_ok() {
+        return Err(format!("{} already exists", dst.display()));
+    }
+
+    let copied = match deployer::deploy(src, &dst, target.mode) {
+        Ok(copied) => copied,
+        Err(e) => {
+            let _ = deployer::undeploy(&dst, target.mode);
+            return Err(e.to_string());
+        }
+    };
+    // Files shared with the content store are copied rather than linked, so the
+    // deployment no longer follows the install the way a hardlink one would.
+    let mode = if copied > 0 {
+        let _ = app.emit(
+            "terminal-output",
+            format!(
+                "{} files of {} are shared with other installs and were copied; {} is kept as a copy",
+                copied,
+                item.name,
+                dst.display()
+            ),
+        );
+        DeployMode::Copy
+    } else {
+        target.mode
+    };
+
+    Ok(Deployment {
+        path: dst_str,
+        mode,
+        deployed_at: std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_secs(),
+    })
+}
+
+fn remove_deployment(policy: &PathPolicy, deployment: &Deployment) -> Result<(), String> {
+    let path = Path::new(&deployment.path);
+    if fs::symlink_metadata(path).is_err() {
+        return Ok(());
+    }
+    let path = policy.resolve_entry(path)?;
+    deployer::undeploy(&path, deployment.mode).map_err(|e| e.to_string())
+}
+
+fn deploy_policy(app: &AppHandle) -> PathPolicy {
+    let settings = current_settings(app);
+    PathPolicy::new(
+        settings
+            .app_profiles
+            .values()
+            .filter_map(|p| p.deploy.as_ref())
+            .map(|t| PathBuf::from(&t.dir))
+            .collect::<Vec<_>>(),
+    )
+}
+
+fn deploy_target_for(app: &AppHandle, steam_id: &str) -> Option<DeployTarget> {
+    let target: SteamTarget = steam_id.parse().ok()?;
+    current_settings(app)
+        .profile(&target.app_id().to_string())
+        .deploy
+}
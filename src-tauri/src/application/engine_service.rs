@@ -0,0 +1,52 @@
+use crate::domain::steamcmd_output::EngineStart;
+use crate::domain::steamcmd_script::SteamCmdScript;
+use crate::domain::types::{EngineState, ProcessManagerState};
+use crate::infrastructure::bin_loader::{
+    current_engine_state, lock_engine, report_engine_start, set_engine_state,
+};
+use crate::infrastructure::steam_client::execute_steamcmd_with_progress;
+use std::thread;
+use tauri::{AppHandle, Manager};
+
+/// Sets up steamcmd and lets it update itself on a background thread, so the first
+/// download does not sit at 0% meanwhile. Progress is reported through `engine-state`.
+pub fn prepare_engine(app: &AppHandle) -> Result<(), String> {
+    if current_engine_state(app) == EngineState::Ready {
+        return Ok(());
+    }
+    let app = app.clone();
+    thread::spawn(move || {
+        let _engine = lock_engine();
+        // A download may have started steamcmd while this waited.
+        if current_engine_state(&app) == EngineState::Ready {
+            return;
+        }
+        // Only held while spawning, so closing the app can still stop the process.
+        let started = {
+            let state = app.state::<ProcessManagerState>();
+            let process_manager = match state.0.lock() {
+                Ok(manager) => manager,
+                Err(_) => return,
+            };
+            execute_steamcmd_with_progress(
+                &app,
+                &SteamCmdScript::new(),
+                &process_manager,
+                String::new(),
+            )
+        };
+        match started.map(|run| run.result.recv()) {
+            Ok(Ok(result)) => report_engine_start(&app, result.engine_start()),
+            Ok(Err(_)) => report_engine_start(&app, EngineStart::Broken),
+            Err(e) => {
+                report_engine_start(&app, EngineStart::Broken);
+                set_engine_state(&app, EngineState::Failed(e));
+            }
+        }
+    });
+    Ok(())
+}
+
+pub fn get_engine_state(app: &AppHandle) -> EngineState {
+    current_engine_state(app)
+}
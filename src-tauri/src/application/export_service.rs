@@ -0,0 +1,130 @@
+use crate::domain::steamcmd_script::SteamTarget;
+use crate::domain::types::{ArchiveFormat, ExportEntry, ExportManifest, HistoryState};
+use crate::infrastructure::archiver::{total_size, write_archive, ArchiveSource};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::thread;
+use tauri::{AppHandle, Emitter};
+
+/// Validates the request, then packs the archive on a background thread, reporting
+/// through `export-progress`, `export-complete` and `export-failed`.
+pub fn export_items(
+    app: &AppHandle,
+    state: &HistoryState,
+    ids: &[String],
+    output_path: &str,
+    format: ArchiveFormat,
+) -> Result<(), String> {
+    let output = PathBuf::from(output_path);
+    if !output.is_absolute() {
+        return Err("Output path must be absolute".to_string());
+    }
+    let extension = format!(".{}", format.extension());
+    if !output
+        .to_string_lossy()
+        .to_ascii_lowercase()
+        .ends_with(&extension)
+    {
+        return Err(format!(
+            "The file name must end in {} for this format",
+            extension
+        ));
+    }
+    if output.exists() {
+        return Err(format!("{} already exists", output.display()));
+    }
+    if !output.parent().is_some_and(Path::is_dir) {
+        return Err("Output folder does not exist".to_string());
+    }
+    if ids.is_empty() {
+        return Err("Nothing to export".to_string());
+    }
+
+    let history = state.0.lock().map_err(|_| "Failed to lock mutex")?;
+    let mut sources: Vec<ArchiveSource> = Vec::new();
+    let mut entries = Vec::new();
+    for id in ids {
+        let item = history
+            .iter()
+            .find(|h| &h.id == id)
+            .ok_or_else(|| format!("History item not found: {}", id))?;
+        let dir = PathBuf::from(&item.install_path);
+        if item.install_path.is_empty() || !dir.is_dir() {
+            return Err(format!("Install folder of {} is missing", item.name));
+        }
+
+        let base = dir
+            .file_name()
+            .map(|n| n.to_string_lossy().to_string())
+            .unwrap_or_else(|| item.steam_id.replace(':', "_"));
+        let mut folder = base.clone();
+        let mut n = 2;
+        while sources.iter().any(|s| s.prefix == folder) {
+            folder = format!("{} ({})", base, n);
+            n += 1;
+        }
+
+        entries.push(ExportEntry {
+            steam_id: item.steam_id.clone(),
+            title: item.name.clone(),
+            app_id: item
+                .steam_id
+                .parse::<SteamTarget>()
+                .ok()
+                .map(|t| t.app_id().to_string()),
+            time_updated: item.time_updated,
+            folder: folder.clone(),
+        });
+        sources.push(ArchiveSource {
+            dir,
+            prefix: folder,
+        });
+    }
+    drop(history);
+
+    let manifest = ExportManifest {
+        exported_at: std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_secs(),
+        items: entries,
+    };
+    let manifest = serde_json::to_vec_pretty(&manifest).map_err(|e| e.to_string())?;
+
+    let app = app.clone();
+    thread::spawn(move || {
+        let output_str = output.to_string_lossy().to_string();
+        let total = total_size(&sources).unwrap_or(0);
+        let mut last_percent = None;
+        let result = write_archive(&output, format, &manifest, &sources, |done| {
+            let percent = done.saturating_mul(100).checked_div(total).unwrap_or(100);
+            if last_percent != Some(percent) {
+                last_percent = Some(percent);
+                let _ = app.emit(
+                    "export-progress",
+                    serde_json::json!({
+                        "output": output_str,
+                        "done": done,
+                        "total": total
+                    }),
+                );
+            }
+        });
+        match result {
+            Ok(()) => {
+                let _ = app.emit(
+                    "export-complete",
+                    serde_json::json!({ "output": output_str }),
+                );
+            }
+            Err(e) => {
+                let _ = fs::remove_file(&output);
+                let _ = app.emit(
+                    "export-failed",
+                    serde_json::json!({ "output": output_str, "error": e.to_string() }),
+                );
+            }
+        }
+    });
+    Ok(())
+}
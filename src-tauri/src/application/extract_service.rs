@@ -0,0 +1,66 @@
+use crate::application::library_service::path_policy;
+use crate::application::settings_service::current_settings;
+use crate::domain::gma::GmaHeader;
+use crate::domain::types::{DownloadItem, ExtractMode, HistoryState};
+use crate::infrastructure::archiver::{extract_archive, find_archives, read_gma};
+use std::fs;
+use std::path::Path;
+use tauri::{AppHandle, Emitter, Manager};
+
+/// An explicit choice on the item wins, then the one made for earlier downloads of
+/// it, then the app profile.
+pub fn extract_mode_for(app: &AppHandle, item: &DownloadItem, app_id: &str) -> ExtractMode {
+    item.extract
+        .or_else(|| previous_extract_mode(app, &item.steam_id))
+        .or(current_settings(app).profile(app_id).extract)
+        .unwrap_or_default()
+}
+
+pub fn previous_extract_mode(app: &AppHandle, steam_id: &str) -> Option<ExtractMode> {
+    let state = app.state::<HistoryState>();
+    let history = state.0.lock().ok()?;
+    history
+        .iter()
+        .rev()
+        .find(|h| h.steam_id == steam_id)
+        .and_then(|h| h.extract)
+}
+
+/// Unpacks the archives in downloaded content next to where they were found. An
+/// archive that fails to unpack is kept and reported; the download still succeeds.
+pub fn extract_downloaded(app: &AppHandle, dir: &Path, mode: ExtractMode) -> Result<(), String> {
+    if mode == ExtractMode::Off {
+        return Ok(());
+    }
+    for archive in find_archives(dir).map_err(|e| e.to_string())? {
+        let name = archive
+            .file_name()
+            .map(|n| n.to_string_lossy().to_string())
+            .unwrap_or_default();
+        let dest = archive.parent().unwrap_or(dir);
+        match extract_archive(&archive, dest) {
+            Ok(count) => {
+                let _ = app.emit(
+                    "terminal-output",
+                    format!("Extracted {} files from {}", count, name),
+                );
+                if mode == ExtractMode::DeleteArchive {
+                    fs::remove_file(&archive).map_err(|e| e.to_string())?;
+                }
+            }
+            Err(e) => {
+                let _ = app.emit(
+                    "terminal-output",
+                    format!("Failed to extract {}: {}", name, e),
+                );
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Lists a `.gma` or `_legacy.bin` addon without unpacking it.
+pub fn list_gma(app: &AppHandle, path: &str) -> Result<GmaHeader, String> {
+    let path = path_policy(app)?.resolve(Path::new(path))?;
+    read_gma(&path).map_err(|e| e.to_string())
+}
@@ -1,8 +1,8 @@
-use crate::domain::types::{HistoryItem, HistoryState};
-use crate::infrastructure::persistence::{load_history, save_history};
-use std::fs;
+use crate::application::{dedupe_service, deploy_service, trash_service};
+use crate::domain::types::{HistoryItem, HistoryState, TrashState};
+use crate::infrastructure::persistence::{load_history, remove_manifest, save_history};
 use std::path::Path;
-use tauri::AppHandle;
+use tauri::{AppHandle, Emitter};
 
 pub fn check_file_exists(path: &str) -> bool {
     Path::new(path).exists()
@@ -31,6 +31,41 @@ pub fn add_history_item(
     Ok(())
 }
 
+/// Adds a completed download. Earlier entries for the same item in the same folder
+/// describe contents that were just replaced, so they are dropped along with their
+/// manifests; their deployments and per-item settings carry over. Returns whether any
+/// deployments did.
+pub fn replace_history_item(
+    app: &AppHandle,
+    state: &HistoryState,
+    mut item: HistoryItem,
+) -> Result<bool, String> {
+    let mut history = state.0.lock().map_err(|_| "Failed to lock mutex")?;
+    let (previous, rest): (Vec<HistoryItem>, Vec<HistoryItem>) = history.drain(..).partition(|h| {
+        h.id != item.id
+            && h.steam_id == item.steam_id
+            && !h.install_path.is_empty()
+            && h.install_path == item.install_path
+    });
+    *history = rest;
+    for old in previous {
+        for deployment in old.deployments {
+            if !item.deployments.iter().any(|d| d.path == deployment.path) {
+                item.deployments.push(deployment);
+            }
+        }
+        item.keep_versions = old.keep_versions.or(item.keep_versions);
+        item.extract = item.extract.or(old.extract);
+        let _ = remove_manifest(app, &old.id);
+    }
+    let carried = !item.deployments.is_empty();
+    history.retain(|x| x.id != item.id);
+    history.push(item);
+
+    save_history(app, &history)?;
+    Ok(carried)
+}
+
 pub fn get_all_history(state: &HistoryState) -> Result<Vec<HistoryItem>, String> {
     let history = state.0.lock().map_err(|_| "Failed to lock mutex")?;
     // Return copy
@@ -39,21 +74,40 @@ pub fn get_all_history(state: &HistoryState) -> Result<Vec<HistoryItem>, String>
 
 pub fn clear_all_history(app: &AppHandle, state: &HistoryState) -> Result<(), String> {
     let mut history = state.0.lock().map_err(|_| "Failed to lock mutex")?;
+    for item in history.iter_mut() {
+        let _ = deploy_service::undeploy_all(app, item);
+        let _ = remove_manifest(app, &item.id);
+    }
     history.clear();
     save_history(app, &history)?;
     Ok(())
 }
 
-pub fn remove_item(app: &AppHandle, state: &HistoryState, id: &str) -> Result<(), String> {
+pub fn remove_item(
+    app: &AppHandle,
+    state: &HistoryState,
+    trash: &TrashState,
+    id: &str,
+    keep_files: bool,
+) -> Result<(), String> {
     let mut history = state.0.lock().map_err(|_| "Failed to lock mutex")?;
     if let Some(pos) = history.iter().position(|x| x.id == id) {
-        let item = history.remove(pos);
-
-        if Path::new(&item.install_path).exists() {
-            let _ = fs::remove_dir_all(&item.install_path);
+        let mut item = history[pos].clone();
+        // A deployment that cannot be removed (e.g. a file open in the game) must not
+        // keep the item in history; it stays recorded on the trash entry instead.
+        if let Err(e) = deploy_service::undeploy_all(app, &mut item) {
+            let _ = app.emit("terminal-output", format!("Undeploy failed: {}", e));
         }
-
+        let install_path = item.install_path.clone();
+        trash_service::move_to_trash(app, trash, item, keep_files)?;
+        history.remove(pos);
         save_history(app, &history)?;
+        drop(history);
+        // The trash may be on another filesystem, in which case the move copied the
+        // files out and dropped their store links.
+        if !keep_files {
+            dedupe_service::collect_garbage_for(app, [Path::new(&install_path)]);
+        }
     }
     Ok(())
 }
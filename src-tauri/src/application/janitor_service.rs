@@ -0,0 +1,114 @@
+use crate::application::commands::AppState;
+use crate::application::library_service::download_root;
+use crate::domain::steamcmd_script::SteamTarget;
+use crate::domain::types::{HistoryState, Status, TempFolder};
+use crate::infrastructure::file_manager;
+use crate::infrastructure::persistence::load_move_journals;
+use std::collections::HashSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+use tauri::{AppHandle, Manager};
+
+/// Staging folders (`download/<content id>` holding a `steamapps` tree) that no
+/// active queue item or pending move owns. Re-queueing `steam_id` downloads into the
+/// same folder, so steamcmd continues from the partial data.
+pub fn scan_temp_folders(app: &AppHandle) -> Result<Vec<TempFolder>, String> {
+    let root = download_root(app)?;
+    let entries = match fs::read_dir(&root) {
+        Ok(entries) => entries,
+        Err(_) => return Ok(Vec::new()),
+    };
+    let owned = owned_temp_dirs(app, &root)?;
+
+    let mut folders = Vec::new();
+    for entry in entries.flatten() {
+        let path = entry.path();
+        let content_id = entry.file_name().to_string_lossy().to_string();
+        if !is_temp_dir(&path, &content_id) || owned.contains(&path) {
+            continue;
+        }
+        folders.push(TempFolder {
+            steam_id: infer_target(&path, &content_id).map(|t| t.to_string()),
+            size_bytes: file_manager::dir_stats(&path).map(|(b, _)| b).unwrap_or(0),
+            path: path.to_string_lossy().to_string(),
+            content_id,
+        });
+    }
+    folders.sort_by(|a, b| a.content_id.cmp(&b.content_id));
+    Ok(folders)
+}
+
+/// Deletes the given leftover folders, or all of them when `paths` is `None`.
+/// Returns the number of bytes freed.
+pub fn clean_temp_folders(app: &AppHandle, paths: Option<Vec<String>>) -> Result<u64, String> {
+    let folders = scan_temp_folders(app)?;
+    let mut freed = 0;
+    for folder in folders {
+        if paths.as_ref().is_some_and(|p| !p.contains(&folder.path)) {
+            continue;
+        }
+        fs::remove_dir_all(&folder.path).map_err(|e| e.to_string())?;
+        freed += folder.size_bytes;
+    }
+    Ok(freed)
+}
+
+fn is_temp_dir(path: &Path, name: &str) -> bool {
+    !name.is_empty() && name.chars().all(|c| c.is_ascii_digit()) && path.join("steamapps").is_dir()
+}
+
+// App downloads are installed in place, so their staging folder is also a history
+// entry's install folder.
+fn owned_temp_dirs(app: &AppHandle, root: &Path) -> Result<HashSet<PathBuf>, String> {
+    let mut owned: HashSet<PathBuf> = load_move_journals(app)?
+        .into_iter()
+        .flatten()
+        .map(|j| PathBuf::from(j.temp_dir))
+        .collect();
+    let history = app.state::<HistoryState>();
+    owned.extend(
+        history
+            .0
+            .lock()
+            .map_err(|_| "Failed to lock mutex")?
+            .iter()
+            .filter(|h| !h.install_path.is_empty())
+            .map(|h| PathBuf::from(&h.install_path)),
+    );
+    let state = app.state::<AppState>();
+    let q = state.0.lock().map_err(|_| "Poisoned mutex")?;
+    for item in &q.items {
+        if matches!(item.status, Status::Completed | Status::Failed(_)) {
+            continue;
+        }
+        if let Ok(target) = item.steam_id.parse::<SteamTarget>() {
+            owned.insert(root.join(target.content_id()));
+        }
+    }
+    Ok(owned)
+}
+
+// steamcmd lays workshop items out under `steamapps/workshop/{downloads,content}/<app>/`
+// and apps under `steamapps/appmanifest_<app>.acf`.
+fn infer_target(path: &Path, content_id: &str) -> Option<SteamTarget> {
+    let steamapps = path.join("steamapps");
+    for stage in ["content", "downloads"] {
+        let apps = match fs::read_dir(steamapps.join("workshop").join(stage)) {
+            Ok(apps) => apps,
+            Err(_) => continue,
+        };
+        for app_dir in apps.flatten() {
+            if app_dir.path().join(content_id).exists() {
+                let app_id = app_dir.file_name().to_string_lossy().to_string();
+                return format!("{}:{}", app_id, content_id).parse().ok();
+            }
+        }
+    }
+    if steamapps
+        .join(format!("appmanifest_{}.acf", content_id))
+        .exists()
+    {
+        return content_id.parse().ok();
+    }
+    None
+}
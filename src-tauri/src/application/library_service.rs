@@ -0,0 +1,445 @@
+use crate::application::commands::AppState;
+use crate::application::dedupe_service::collect_garbage_for;
+use crate::application::deploy_service::undeploy_all;
+use crate::application::history_service::add_history_item;
+use crate::application::settings_service::current_settings;
+use crate::application::trash_service::trash_root;
+use crate::application::verify_service::record_manifest;
+use crate::application::version_service::versions_dir;
+use crate::domain::library::{file_id_from_folder_name, reconcile, ScannedFolder};
+use crate::domain::steamcmd_script::SteamTarget;
+use crate::domain::template::{render_folder_path, TemplateVars};
+use crate::domain::types::{
+    CollisionPolicy, DownloadItem, HistoryItem, HistoryState, InstallMarker, LibraryReport, Status,
+};
+use crate::infrastructure::file_manager;
+use crate::infrastructure::path_policy::PathPolicy;
+use crate::infrastructure::persistence::{
+    load_marker, load_move_journals, remove_manifest, save_history, save_marker,
+};
+use crate::infrastructure::workshop_api::fetch_details;
+use std::collections::HashSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+use tauri::{AppHandle, Emitter, Manager};
+
+const MAX_SCAN_DEPTH: usize = 4;
+
+/// steamcmd staging area, and the library root used when none is configured.
+pub fn download_root(app: &AppHandle) -> Result<PathBuf, String> {
+    Ok(app
+        .path()
+        .app_data_dir()
+        .map_err(|e| e.to_string())?
+        .join("download"))
+}
+
+pub fn library_roots(app: &AppHandle) -> Result<Vec<PathBuf>, String> {
+    let settings = current_settings(app);
+    let mut roots: Vec<PathBuf> = Vec::new();
+    let configured = settings
+        .download_roots
+        .iter()
+        .chain(
+            settings
+                .app_profiles
+                .values()
+                .filter_map(|p| p.root.as_ref()),
+        )
+        .map(PathBuf::from)
+        .chain(std::iter::once(download_root(app)?));
+    for root in configured {
+        if !roots.contains(&root) {
+            roots.push(root);
+        }
+    }
+    Ok(roots)
+}
+
+pub fn path_policy(app: &AppHandle) -> Result<PathPolicy, String> {
+    let mut roots = library_roots(app)?;
+    roots.push(trash_root(app)?);
+    Ok(PathPolicy::new(roots))
+}
+
+/// Final folder for an item: queue-time destination, else the app profile root, else
+/// the first configured root, joined with the rendered folder template.
+pub fn resolve_install_dir(
+    app: &AppHandle,
+    item: &DownloadItem,
+    target: SteamTarget,
+) -> Result<PathBuf, String> {
+    let settings = current_settings(app);
+    let app_id = target.app_id().to_string();
+    let profile = settings.profile(&app_id);
+
+    let root = match item
+        .destination
+        .as_ref()
+        .or(profile.root.as_ref())
+        .or(settings.download_roots.first())
+    {
+        Some(root) => PathBuf::from(root),
+        None => download_root(app)?,
+    };
+    let template = profile
+        .folder_template
+        .as_deref()
+        .unwrap_or(&settings.folder_template);
+    let relative = render_folder_path(
+        template,
+        &TemplateVars {
+            app_id: &app_id,
+            app_name: item.app_name.as_deref().unwrap_or(&app_id),
+            title: &item.name,
+            file_id: &target.content_id(),
+        },
+    );
+    let dir = root.join(relative);
+    check_not_staging(app, &dir)?;
+    Ok(dir)
+}
+
+/// steamcmd stages each download in `download/<content id>` and that folder is
+/// deleted after the move, so an install folder must never be one of them or lie
+/// inside one, e.g. with a `{file_id}` template on the default root.
+fn check_not_staging(app: &AppHandle, dir: &Path) -> Result<(), String> {
+    let staging_root = download_root(app)?;
+    let first = dir
+        .strip_prefix(&staging_root)
+        .ok()
+        .and_then(|rest| rest.components().next());
+    if let Some(first) = first {
+        let name = first.as_os_str().to_string_lossy();
+        if !name.is_empty() && name.chars().all(|c| c.is_ascii_digit()) {
+            return Err(format!(
+                "{} would overlap a steamcmd staging folder; change the folder template or \
+                 pick a download root",
+                dir.display()
+            ));
+        }
+    }
+    Ok(())
+}
+
+pub enum InstallSlot {
+    Free(PathBuf),
+    /// Already holds this item; update it in place.
+    Owned(PathBuf),
+    /// Holds a different item that the overwrite policy replaces; see
+    /// [`release_install_dir`].
+    Displaced(PathBuf),
+}
+
+/// Picks the folder an item is moved into. A folder holding the same steam id is
+/// updated in place; one that belongs to anything else is handled per `policy`.
+pub fn claim_install_dir(
+    app: &AppHandle,
+    dir: PathBuf,
+    steam_id: &str,
+    file_id: &str,
+    policy: CollisionPolicy,
+) -> Result<InstallSlot, String> {
+    let slot = |path: PathBuf| match folder_owner(app, &path) {
+        _ if !path.exists() => Some(InstallSlot::Free(path)),
+        Some(owner) if owner == steam_id => Some(InstallSlot::Owned(path)),
+        _ => None,
+    };
+    if let Some(slot) = slot(dir.clone()) {
+        return Ok(slot);
+    }
+
+    match policy {
+        CollisionPolicy::Overwrite => Ok(InstallSlot::Displaced(dir)),
+        CollisionPolicy::Fail => Err(format!("{} already holds a different item", dir.display())),
+        CollisionPolicy::Suffix => {
+            let name = dir
+                .file_name()
+                .map(|n| n.to_string_lossy().to_string())
+                .unwrap_or_default();
+            let mut n = 1;
+            loop {
+                let candidate = if n == 1 {
+                    format!("{} [{}]", name, file_id)
+                } else {
+                    format!("{} [{}] ({})", name, file_id, n)
+                };
+                if let Some(slot) = slot(dir.with_file_name(candidate)) {
+                    return Ok(slot);
+                }
+                n += 1;
+            }
+        }
+    }
+}
+
+/// Forgets whatever `dir` held before an overwrite: its history entries, their
+/// manifests and deployments, and the versions kept for it, which would otherwise
+/// all describe contents that are gone.
+pub fn release_install_dir(app: &AppHandle, dir: &Path) -> Result<(), String> {
+    let state = app.state::<HistoryState>();
+    let mut history = state.0.lock().map_err(|_| "Failed to lock mutex")?;
+    let (mut displaced, kept): (Vec<_>, Vec<_>) = std::mem::take(&mut *history)
+        .into_iter()
+        .partition(|h| !h.install_path.is_empty() && Path::new(&h.install_path) == dir);
+    *history = kept;
+    save_history(app, &history)?;
+    drop(history);
+
+    for item in &mut displaced {
+        if let Err(e) = undeploy_all(app, item) {
+            let _ = app.emit("terminal-output", format!("Undeploy failed: {}", e));
+        }
+        let _ = remove_manifest(app, &item.id);
+        let _ = app.emit(
+            "terminal-output",
+            format!("Replacing {} in {}", item.name, dir.display()),
+        );
+    }
+    let versions = versions_dir(dir);
+    if versions.exists() {
+        fs::remove_dir_all(&versions).map_err(|e| e.to_string())?;
+    }
+    Ok(())
+}
+
+fn folder_owner(app: &AppHandle, dir: &Path) -> Option<String> {
+    if let Some(marker) = load_marker(dir) {
+        return Some(marker.steam_id);
+    }
+    let state = app.try_state::<HistoryState>()?;
+    let history = state.0.lock().ok()?;
+    history
+        .iter()
+        .find(|h| Path::new(&h.install_path) == dir)
+        .map(|h| h.steam_id.clone())
+}
+
+pub fn open_folder(app: &AppHandle, path: &str) -> Result<(), String> {
+    path_policy(app)?.resolve(Path::new(path))?;
+    file_manager::open_path(path).map_err(|e| e.to_string())
+}
+
+pub fn path_exists(app: &AppHandle, path: &str) -> Result<bool, String> {
+    path_policy(app)?.check_target(Path::new(path))?;
+    Ok(file_manager::check_path_exists(path))
+}
+
+pub fn reconcile_library(app: &AppHandle, state: &HistoryState) -> Result<LibraryReport, String> {
+    let history = state.0.lock().map_err(|_| "Failed to lock mutex")?;
+    let known: HashSet<PathBuf> = history
+        .iter()
+        .filter(|h| !h.install_path.is_empty())
+        .map(|h| PathBuf::from(&h.install_path))
+        .collect();
+    let mut folders = Vec::new();
+    for root in library_roots(app)? {
+        if root.is_dir() {
+            scan_dir(&root, &known, 0, &mut folders)?;
+        }
+    }
+    Ok(reconcile(
+        &history,
+        folders,
+        file_manager::check_path_exists,
+    ))
+}
+
+pub fn adopt_orphan(
+    app: &AppHandle,
+    state: &HistoryState,
+    path: &str,
+    steam_id: Option<String>,
+) -> Result<HistoryItem, String> {
+    let dir = managed_folder(app, path)?;
+    let marker = load_marker(&dir);
+    let folder_name = dir
+        .file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .unwrap_or_default();
+    let mut title = None;
+    let steam_id = match steam_id.or_else(|| marker.as_ref().map(|m| m.steam_id.clone())) {
+        Some(steam_id) => steam_id,
+        None => {
+            // The folder name only gives the file id; ask Steam which app it belongs to.
+            let file_id = file_id_from_folder_name(&folder_name).ok_or(
+                "Could not determine the Steam ID for this folder; enter it as <app id>:<file id>",
+            )?;
+            let details = fetch_details(file_id.parse()?)
+                .map_err(|e| format!("Could not look up workshop item {}: {}", file_id, e))?;
+            title = Some(details.title).filter(|t| !t.is_empty());
+            format!("{}:{}", details.app_id, file_id)
+        }
+    };
+    let target: SteamTarget = steam_id.parse()?;
+    if !matches!(target, SteamTarget::Workshop { .. }) {
+        return Err(format!(
+            "{} is not a workshop item; enter it as <app id>:<file id>",
+            steam_id
+        ));
+    }
+    let steam_id = target.to_string();
+    let name = marker
+        .map(|m| m.name)
+        .or(title)
+        .unwrap_or_else(|| folder_name.clone());
+
+    save_marker(
+        &dir,
+        &InstallMarker {
+            steam_id: steam_id.clone(),
+            name: name.clone(),
+        },
+    )?;
+
+    let (size_bytes, file_count) = file_manager::dir_stats(&dir).map_err(|e| e.to_string())?;
+    let item = HistoryItem {
+        id: uuid::Uuid::new_v4().to_string(),
+        steam_id,
+        name,
+        install_path: dir.to_string_lossy().to_string(),
+        folder_name,
+        timestamp: std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map_err(|e| e.to_string())?
+            .as_secs(),
+        size_bytes,
+        file_count,
+        ..Default::default()
+    };
+    record_manifest(app, &item.id, &dir)?;
+    add_history_item(app, state, item.clone())?;
+    Ok(item)
+}
+
+pub fn delete_orphan(app: &AppHandle, state: &HistoryState, path: &str) -> Result<(), String> {
+    let dir = managed_folder(app, path)?;
+    let history = state.0.lock().map_err(|_| "Failed to lock mutex")?;
+    if history
+        .iter()
+        .any(|h| !h.install_path.is_empty() && Path::new(&h.install_path).starts_with(&dir))
+    {
+        return Err("Folder contains a history entry".to_string());
+    }
+    drop(history);
+    check_not_staging(app, &dir)?;
+    check_not_in_use(app, &dir)?;
+    fs::remove_dir_all(&dir).map_err(|e| e.to_string())?;
+    collect_garbage_for(app, [dir.as_path()]);
+    Ok(())
+}
+
+/// Refuses folders an unfinished move or a queued download still writes to or resumes
+/// from.
+fn check_not_in_use(app: &AppHandle, dir: &Path) -> Result<(), String> {
+    let overlaps = |other: &Path| other.starts_with(dir) || dir.starts_with(other);
+    for journal in load_move_journals(app)?.into_iter().flatten() {
+        let dst = Path::new(&journal.dst);
+        let mut owned = [&journal.src, &journal.temp_dir]
+            .into_iter()
+            .chain(journal.snapshot.as_ref())
+            .map(PathBuf::from)
+            .chain([dst.to_path_buf()])
+            .chain(file_manager::replace_siblings(dst));
+        if owned.any(|p| overlaps(&p)) {
+            return Err(format!(
+                "{} is part of an unfinished move of {}",
+                dir.display(),
+                journal.item.name
+            ));
+        }
+    }
+
+    let items: Vec<DownloadItem> = {
+        let state = app.state::<AppState>();
+        let q = state.0.lock().map_err(|_| "Poisoned mutex")?;
+        q.items
+            .iter()
+            .filter(|i| !matches!(i.status, Status::Completed | Status::Failed(_)))
+            .cloned()
+            .collect()
+    };
+    for item in items {
+        let Ok(target) = item.steam_id.parse::<SteamTarget>() else {
+            continue;
+        };
+        let install_dir = match &item.install_path {
+            Some(path) => PathBuf::from(path),
+            None => match resolve_install_dir(app, &item, target) {
+                Ok(dir) => dir,
+                Err(_) => continue,
+            },
+        };
+        if overlaps(&install_dir) {
+            return Err(format!(
+                "{} is the install folder of queued download {}",
+                dir.display(),
+                item.name
+            ));
+        }
+    }
+    Ok(())
+}
+
+// Collects install folders and orphans below `dir`. Folders that only group installs
+// (e.g. `{app_name}/` from a template) are descended into rather than reported.
+fn scan_dir(
+    dir: &Path,
+    known: &HashSet<PathBuf>,
+    depth: usize,
+    out: &mut Vec<ScannedFolder>,
+) -> Result<(), String> {
+    for entry in fs::read_dir(dir).map_err(|e| e.to_string())? {
+        let entry = entry.map_err(|e| e.to_string())?;
+        let path = entry.path();
+        let folder_name = entry.file_name().to_string_lossy().to_string();
+        if !path.is_dir() || folder_name.starts_with('.') || path.join("steamapps").exists() {
+            continue;
+        }
+        if out.iter().any(|f| Path::new(&f.path) == path) {
+            continue;
+        }
+
+        let marker = load_marker(&path);
+        let is_group = marker.is_none()
+            && !known.contains(&path)
+            && depth < MAX_SCAN_DEPTH
+            && contains_install(&path, known, MAX_SCAN_DEPTH - depth);
+        if is_group {
+            scan_dir(&path, known, depth + 1, out)?;
+        } else {
+            out.push(ScannedFolder {
+                marker,
+                size_bytes: file_manager::dir_stats(&path).map(|(b, _)| b).unwrap_or(0),
+                path: path.to_string_lossy().to_string(),
+                folder_name,
+            });
+        }
+    }
+    Ok(())
+}
+
+fn contains_install(dir: &Path, known: &HashSet<PathBuf>, depth: usize) -> bool {
+    if known.iter().any(|k| k != dir && k.starts_with(dir)) {
+        return true;
+    }
+    if depth == 0 {
+        return false;
+    }
+    fs::read_dir(dir)
+        .map(|entries| {
+            entries.flatten().map(|e| e.path()).any(|p| {
+                p.is_dir() && (load_marker(&p).is_some() || contains_install(&p, known, depth - 1))
+            })
+        })
+        .unwrap_or(false)
+}
+
+fn managed_folder(app: &AppHandle, path: &str) -> Result<PathBuf, String> {
+    let dir = PathBuf::from(path);
+    path_policy(app)?.resolve(&dir)?;
+    if !dir.is_dir() {
+        return Err(format!("Not a folder: {}", path));
+    }
+    Ok(dir)
+}
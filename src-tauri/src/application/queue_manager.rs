@@ -1,24 +1,86 @@
+use crate::application::account_service::{account_credentials, login_credentials};
 use crate::application::commands::AppState;
-use crate::application::history_service::add_history_item;
-use crate::domain::sanitizer::sanitize_name;
-use crate::domain::types::{DownloadItem, HistoryItem, HistoryState, ProcessManagerState, Status};
+use crate::application::dedupe_service::{collect_garbage_for, dedupe_install, unshare_version};
+use crate::application::deploy_service::{auto_deploy, refresh_deployments};
+use crate::application::extract_service::{
+    extract_downloaded, extract_mode_for, previous_extract_mode,
+};
+use crate::application::history_service::{add_history_item, replace_history_item};
+use crate::application::library_service::{
+    claim_install_dir, download_root, release_install_dir, resolve_install_dir, InstallSlot,
+};
+use crate::application::settings_service::current_settings;
+use crate::application::startup_service::report_problem;
+use crate::application::update_service::{
+    keep_workshop_state, stage_existing_install, staging_size,
+};
+use crate::application::verify_service::record_manifest;
+use crate::application::version_service::{keep_versions_for, prepare_snapshot, prune_versions};
+use crate::domain::steamcmd_output::EngineStart;
+use crate::domain::steamcmd_script::{SteamCmdScript, SteamTarget};
+use crate::domain::types::{
+    DownloadFailure, DownloadItem, FailureClass, HistoryItem, HistoryState, InstallMarker,
+    MoveJournal, Outcome, ProcessManagerState, Status,
+};
+use crate::infrastructure::bin_loader::{lock_engine, report_engine_start};
+use crate::infrastructure::credential_store::SteamCredentials;
 use crate::infrastructure::file_manager;
-use crate::infrastructure::steam_client::execute_steamcmd_with_progress;
+use crate::infrastructure::persistence::{
+    load_marker, load_move_journals, remove_move_journal, save_marker, save_move_journal,
+};
+use crate::infrastructure::steam_client::{execute_steamcmd_with_progress, SteamCmdResult};
 use std::fs;
+use std::path::Path;
 use std::thread;
-use std::time::Duration;
+use std::time::{Duration, Instant};
 use tauri::{AppHandle, Emitter, Manager};
 
 pub fn start_worker(app: AppHandle) {
     thread::spawn(move || worker_loop(app));
 }
 
+/// Extra room kept free beyond an item's published size.
+const SPACE_HEADROOM: u64 = 64 * 1024 * 1024;
+const SPACE_RECHECK: Duration = Duration::from_secs(30);
+const MOVE_PROGRESS_INTERVAL: Duration = Duration::from_millis(250);
+
 fn worker_loop(app: AppHandle) {
+    let mut last_space_check = Instant::now();
     loop {
+        if last_space_check.elapsed() >= SPACE_RECHECK {
+            release_waiting(&app);
+            last_space_check = Instant::now();
+        }
         if let Some(item) = pick_pending_item(&app) {
+            if let Some((required, available)) = space_shortfall(&app, &item) {
+                set_status(
+                    &app,
+                    &item.id,
+                    Status::WaitingForSpace {
+                        required,
+                        available,
+                    },
+                );
+                let _ = app.emit(
+                    "terminal-output",
+                    format!(
+                        "Not enough disk space for {}: {} bytes needed, {} available",
+                        item.name, required, available
+                    ),
+                );
+                let _ = app.emit("queue-update", ());
+                continue;
+            }
             let _ = app.emit("queue-update", ());
+            let started = Instant::now();
             let result = process_item(&app, item.clone());
-            finalize_item(&app, &item.id, result);
+            let completed = result.is_ok();
+            finalize_item(&app, &item.id, result, started.elapsed());
+            if completed {
+                if let Err(e) = auto_deploy(&app, &app.state::<HistoryState>(), &item.id) {
+                    let _ = app.emit("terminal-output", format!("Deploy failed: {}", e));
+                }
+            }
             let _ = app.emit("queue-update", ());
         } else {
             thread::sleep(Duration::from_secs(1));
@@ -37,88 +99,584 @@ fn pick_pending_item(app: &AppHandle) -> Option<DownloadItem> {
     Some(q.items[index].clone())
 }
 
-fn finalize_item(app: &AppHandle, id: &str, result: Result<String, String>) {
-    let state = match app.try_state::<AppState>() {
-        Some(s) => s,
+fn set_status(app: &AppHandle, id: &str, status: Status) {
+    if let Some(state) = app.try_state::<AppState>() {
+        if let Ok(mut q) = state.0.lock() {
+            if let Some(item) = q.items.iter_mut().find(|i| i.id == id) {
+                item.status = status;
+            }
+        }
+    }
+}
+
+// Returns `(required, available)` for the first filesystem the item does not fit on.
+// Items of unknown size are let through. Besides the download itself, the temp
+// filesystem holds an install staged for an update when it cannot be linked there,
+// and the install filesystem keeps the current contents when a version is kept.
+fn space_shortfall(app: &AppHandle, item: &DownloadItem) -> Option<(u64, u64)> {
+    let download = item.file_size? + SPACE_HEADROOM;
+    let target: SteamTarget = item.steam_id.parse().ok()?;
+    let temp_dir = download_root(app).ok()?.join(target.content_id());
+    let final_dir = resolve_install_dir(app, item, target).ok()?;
+
+    let staged = staging_size(app, item, &temp_dir);
+    let kept = load_marker(&final_dir)
+        .filter(|m| m.steam_id == item.steam_id && keep_versions_for(app, &item.steam_id) > 0)
+        .and_then(|_| file_manager::dir_stats(&final_dir).ok())
+        .map_or(0, |(bytes, _)| bytes);
+
+    let mut needs = vec![(temp_dir.as_path(), download + staged)];
+    if file_manager::same_filesystem(&temp_dir, &final_dir).unwrap_or(true) {
+        needs[0].1 += kept;
+    } else {
+        needs.push((final_dir.as_path(), download + kept));
+    }
+    needs.into_iter().find_map(|(dir, required)| {
+        let available = file_manager::available_space(dir).ok()?;
+        (available < required).then_some((required, available))
+    })
+}
+
+// Moves waiting items back to `Pending` once their space is free.
+fn release_waiting(app: &AppHandle) {
+    let waiting: Vec<DownloadItem> = match app.try_state::<AppState>() {
+        Some(state) => match state.0.lock() {
+            Ok(q) => q
+                .items
+                .iter()
+                .filter(|i| matches!(i.status, Status::WaitingForSpace { .. }))
+                .cloned()
+                .collect(),
+            Err(_) => return,
+        },
         None => return,
     };
-    let mut q = match state.0.lock() {
-        Ok(guard) => guard,
-        Err(_) => return,
+    if waiting.is_empty() {
+        return;
+    }
+    for item in waiting {
+        let status = match space_shortfall(app, &item) {
+            Some((required, available)) => Status::WaitingForSpace {
+                required,
+                available,
+            },
+            None => Status::Pending,
+        };
+        set_status(app, &item.id, status);
+    }
+    let _ = app.emit("queue-update", ());
+}
+
+fn fs_failure(e: std::io::Error) -> DownloadFailure {
+    let class = if file_manager::is_disk_full(&e) {
+        FailureClass::DiskFull
+    } else {
+        FailureClass::FileSystem
     };
-    if let Some(item) = q.items.iter_mut().find(|i| i.id == id) {
-        match result {
-            Ok(path) => {
+    DownloadFailure::new(class, e.to_string())
+}
+
+struct CompletedDownload {
+    install_path: String,
+    reported_size: Option<u64>,
+    flattened: Option<String>,
+}
+
+fn finalize_item(
+    app: &AppHandle,
+    id: &str,
+    result: Result<CompletedDownload, DownloadFailure>,
+    elapsed: Duration,
+) {
+    let item = {
+        let state = match app.try_state::<AppState>() {
+            Some(s) => s,
+            None => return,
+        };
+        let mut q = match state.0.lock() {
+            Ok(guard) => guard,
+            Err(_) => return,
+        };
+        let item = match q.items.iter_mut().find(|i| i.id == id) {
+            Some(item) => item,
+            None => return,
+        };
+        match &result {
+            Ok(done) => {
                 item.status = Status::Completed;
-                item.install_path = Some(path.clone());
-
-                let history_state = app.state::<HistoryState>();
-                let history_item = HistoryItem {
-                    id: item.id.clone(),
-                    steam_id: item.steam_id.clone(),
-                    name: item.name.clone(),
-                    install_path: path,
-                    timestamp: std::time::SystemTime::now()
-                        .duration_since(std::time::UNIX_EPOCH)
-                        .unwrap_or_default()
-                        .as_secs(),
-                };
-                let _ = add_history_item(app, &history_state, history_item);
+                item.install_path = Some(done.install_path.clone());
             }
-            Err(e) => item.status = Status::Failed(e),
+            Err(failure) => item.status = Status::Failed(failure.message.clone()),
         }
+        item.clone()
+    };
+    // A failed move keeps its journal while there is something to resume.
+    let completed = result.is_ok();
+    record_history(app, &item, result, elapsed);
+    if completed {
+        let _ = remove_move_journal(app, id);
     }
 }
 
-fn process_item(app: &AppHandle, item: DownloadItem) -> Result<String, String> {
-    let process_manager_state = app.state::<ProcessManagerState>();
-    let process_manager = process_manager_state.0.lock().map_err(|_| "Lock error")?;
+fn record_history(
+    app: &AppHandle,
+    item: &DownloadItem,
+    result: Result<CompletedDownload, DownloadFailure>,
+    elapsed: Duration,
+) {
+    let mut history_item = HistoryItem {
+        id: item.id.clone(),
+        steam_id: item.steam_id.clone(),
+        name: item.name.clone(),
+        install_path: String::new(),
+        timestamp: unix_now(),
+        duration_secs: elapsed.as_secs(),
+        time_updated: item.time_updated,
+        keep_versions: previous_keep_versions(app, &item.steam_id),
+        extract: item
+            .extract
+            .or_else(|| previous_extract_mode(app, &item.steam_id)),
+        ..Default::default()
+    };
 
-    let (game_id, file_id) = parse_ids(&item.steam_id);
-    let root_dl = app
-        .path()
-        .app_data_dir()
-        .map_err(|e| e.to_string())?
-        .join("download");
-    let temp_dir = root_dl.join(&file_id);
-    let final_dir = root_dl.join(sanitize_name(&item.name));
+    match result {
+        Ok(done) => {
+            let (size_bytes, file_count) =
+                file_manager::dir_stats(Path::new(&done.install_path)).unwrap_or_default();
+            let transferred = done.reported_size.unwrap_or(size_bytes);
+            history_item.avg_speed = (transferred as f64 / elapsed.as_secs_f64().max(1.0)) as u64;
+            history_item.folder_name = Path::new(&done.install_path)
+                .file_name()
+                .map(|n| n.to_string_lossy().to_string())
+                .unwrap_or_default();
+            history_item.install_path = done.install_path;
+            history_item.size_bytes = size_bytes;
+            history_item.file_count = file_count;
+            history_item.reported_size = done.reported_size;
+            history_item.flattened = done.flattened;
+        }
+        Err(failure) => history_item.outcome = Outcome::Failed(failure),
+    }
 
-    let mut commands = vec![
-        format!("force_install_dir \"{}\"", temp_dir.to_string_lossy()),
-        "login anonymous".to_string(),
-    ];
+    let history_state = app.state::<HistoryState>();
+    if history_item.install_path.is_empty() {
+        let _ = add_history_item(app, &history_state, history_item);
+        return;
+    }
+    let id = history_item.id.clone();
+    // Copy and hardlink deployments carried over from the replaced entry still hold
+    // the old files.
+    if let Ok(true) = replace_history_item(app, &history_state, history_item) {
+        if let Err(e) = refresh_deployments(app, &history_state, &id) {
+            let _ = app.emit("terminal-output", format!("Redeploy failed: {}", e));
+        }
+    }
+}
 
-    if let Some(gid) = game_id.as_deref() {
-        commands.push(format!("workshop_download_item {} {}", gid, file_id));
-    } else {
-        commands.push(format!("app_update {} validate", file_id));
+fn previous_keep_versions(app: &AppHandle, steam_id: &str) -> Option<u32> {
+    let history = app.state::<HistoryState>();
+    let history = history.0.lock().ok()?;
+    history
+        .iter()
+        .rev()
+        .find(|h| h.steam_id == steam_id)
+        .and_then(|h| h.keep_versions)
+}
+
+fn unix_now() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs()
+}
+
+/// Runs steamcmd for the item and waits for it. Only a steamcmd that did not get
+/// through its startup is an error here; a failed download is left to the caller.
+fn run_steamcmd(
+    app: &AppHandle,
+    item: &DownloadItem,
+    temp_dir: &Path,
+    target: SteamTarget,
+    account: Option<&SteamCredentials>,
+) -> Result<SteamCmdResult, DownloadFailure> {
+    let script = SteamCmdScript::new()
+        .force_install_dir(temp_dir)
+        .map_err(|e| DownloadFailure::new(FailureClass::FileSystem, e))?;
+    let script = match account {
+        Some(credentials) => script
+            .login_account(&credentials.username, credentials.password.as_deref())
+            .map_err(|e| DownloadFailure::new(FailureClass::Login, e))?,
+        None => script.login_anonymous(),
     }
+    .download(target);
+
+    // Held while steamcmd is extracted, checked and starting up, and let go once it is
+    // ready so another item can start while this one downloads.
+    let mut engine = Some(lock_engine());
+    // Only held while spawning, so closing the app can still stop the process.
+    let run = {
+        let process_manager_state = app.state::<ProcessManagerState>();
+        let process_manager = process_manager_state
+            .0
+            .lock()
+            .map_err(|_| DownloadFailure::new(FailureClass::Unknown, "Lock error"))?;
+        execute_steamcmd_with_progress(app, &script, &process_manager, item.id.clone()).map_err(
+            |e| {
+                report_engine_start(app, EngineStart::Broken);
+                DownloadFailure::new(FailureClass::Engine, e)
+            },
+        )?
+    };
+
+    if run.started.recv().is_ok() {
+        report_engine_start(app, EngineStart::Started);
+        drop(engine.take());
+    }
+    let result = run
+        .result
+        .recv()
+        .map_err(|_| DownloadFailure::new(FailureClass::Engine, "Process crashed"))?;
+    let start = result.engine_start();
+    if let Some(engine) = engine {
+        // Reported before letting go of the engine, which may be rebuilt here.
+        report_engine_start(app, start);
+        drop(engine);
+    }
+
+    match start {
+        EngineStart::Started => Ok(result),
+        EngineStart::UpdateFailed => Err(DownloadFailure::new(
+            FailureClass::Engine,
+            "steamcmd could not update itself; check the network connection",
+        )),
+        _ => Err(result.summary.failure.unwrap_or_else(|| {
+            DownloadFailure::new(FailureClass::Engine, "steamcmd exited while starting up")
+        })),
+    }
+}
 
-    let rx = execute_steamcmd_with_progress(app, commands, &process_manager, item.id.clone())?;
+fn process_item(app: &AppHandle, item: DownloadItem) -> Result<CompletedDownload, DownloadFailure> {
+    let started_at = unix_now();
+    let target: SteamTarget = item
+        .steam_id
+        .parse()
+        .map_err(|e: String| DownloadFailure::new(FailureClass::Unknown, e))?;
+    let root_dl =
+        download_root(app).map_err(|e| DownloadFailure::new(FailureClass::FileSystem, e))?;
+    let temp_dir = root_dl.join(target.content_id());
+    let final_dir = resolve_install_dir(app, &item, target)
+        .map_err(|e| DownloadFailure::new(FailureClass::FileSystem, e))?;
 
-    let success = rx.recv().map_err(|_| "Process crashed")?;
+    if let SteamTarget::Workshop { app_id, file_id } = target {
+        match stage_existing_install(app, &item, app_id, file_id, &temp_dir) {
+            Ok(true) => {
+                let _ = app.emit(
+                    "terminal-output",
+                    format!("Updating {} from the existing install", item.name),
+                );
+            }
+            Ok(false) => {}
+            Err(e) => {
+                let _ = app.emit(
+                    "terminal-output",
+                    format!(
+                        "Could not reuse the existing install, downloading in full: {}",
+                        e
+                    ),
+                );
+            }
+        }
+    }
 
-    if !success {
-        return Err("Download failed".to_string());
+    let account =
+        login_credentials(app).map_err(|e| DownloadFailure::new(FailureClass::Login, e))?;
+    let mut result = run_steamcmd(app, &item, &temp_dir, target, account.as_ref())?;
+    // An anonymous login cannot get what needs ownership; the saved account may.
+    let denied = result
+        .summary
+        .failure
+        .as_ref()
+        .is_some_and(|f| f.class == FailureClass::AccessDenied);
+    if account.is_none() && !result.success && denied {
+        if let Some(credentials) = account_credentials(app) {
+            let _ = app.emit(
+                "terminal-output",
+                format!(
+                    "Access denied anonymously, retrying as {}",
+                    credentials.username
+                ),
+            );
+            result = run_steamcmd(app, &item, &temp_dir, target, Some(&credentials))?;
+        }
+    }
+    if !result.success {
+        let failure = result
+            .summary
+            .failure
+            .unwrap_or_else(|| DownloadFailure::new(FailureClass::Download, "Download failed"));
+        // A partial download on a full disk is of no use and only holds the space.
+        if failure.class == FailureClass::DiskFull {
+            let _ = fs::remove_dir_all(&temp_dir);
+        }
+        return Err(failure);
+    }
+    let reported_size = result.summary.reported_size;
+    if let SteamTarget::Workshop { app_id, file_id } = target {
+        if let Err(e) = keep_workshop_state(app, app_id, file_id, &temp_dir) {
+            let _ = app.emit(
+                "terminal-output",
+                format!("Failed to keep download state for updates: {}", e),
+            );
+        }
     }
 
-    if let Some(gid) = game_id {
+    if let SteamTarget::Workshop { app_id, file_id } = target {
         let content_path = temp_dir
             .join("steamapps/workshop/content")
-            .join(gid)
-            .join(&file_id);
+            .join(app_id.to_string())
+            .join(file_id.to_string());
         if content_path.exists() {
-            file_manager::move_recursive(&content_path, &final_dir).map_err(|e| e.to_string())?;
-            let _ = fs::remove_dir_all(&temp_dir);
-            return Ok(final_dir.to_string_lossy().to_string());
+            let extract = extract_mode_for(app, &item, &app_id.to_string());
+            extract_downloaded(app, &content_path, extract)
+                .map_err(|e| DownloadFailure::new(FailureClass::FileSystem, e))?;
+            let settings = current_settings(app);
+            let policy = settings
+                .profile(&app_id.to_string())
+                .collision_policy
+                .unwrap_or(settings.collision_policy);
+            let slot =
+                claim_install_dir(app, final_dir, &item.steam_id, &file_id.to_string(), policy)
+                    .map_err(|e| DownloadFailure::new(FailureClass::Collision, e))?;
+
+            let flattened = if flatten_for(app, &item, &app_id.to_string()) {
+                let inner = file_manager::wrapper_root(&content_path).map_err(fs_failure)?;
+                let wrapper = inner.strip_prefix(&content_path).unwrap_or(Path::new(""));
+                Some(wrapper.to_string_lossy().replace('\\', "/"))
+            } else {
+                None
+            };
+            let (dst, replace, displaced) = match slot {
+                InstallSlot::Free(dir) => (dir, false, false),
+                InstallSlot::Owned(dir) => (dir, true, false),
+                InstallSlot::Displaced(dir) => (dir, true, true),
+            };
+            if displaced {
+                release_install_dir(app, &dst)
+                    .map_err(|e| DownloadFailure::new(FailureClass::Collision, e))?;
+            }
+            // The contents of a displaced item are not an earlier version of this one.
+            let snapshot = if replace && !displaced {
+                prepare_snapshot(app, &dst, &item.steam_id).unwrap_or_else(|e| {
+                    let _ = app.emit(
+                        "terminal-output",
+                        format!("Failed to keep the previous version: {}", e),
+                    );
+                    None
+                })
+            } else {
+                None
+            };
+            let journal = MoveJournal {
+                item: item.clone(),
+                src: content_path.to_string_lossy().to_string(),
+                dst: dst.to_string_lossy().to_string(),
+                temp_dir: temp_dir.to_string_lossy().to_string(),
+                flattened,
+                replace,
+                reported_size,
+                started_at,
+                snapshot: snapshot.map(|p| p.to_string_lossy().to_string()),
+            };
+            save_move_journal(app, &journal)
+                .map_err(|e| DownloadFailure::new(FailureClass::FileSystem, e))?;
+            let result = complete_move(app, &journal);
+            if result.is_err() {
+                if is_resumable(&journal) {
+                    let _ = app.emit(
+                        "terminal-output",
+                        format!(
+                            "The move of {} will be finished on the next start",
+                            item.name
+                        ),
+                    );
+                } else {
+                    let _ = remove_move_journal(app, &item.id);
+                }
+            }
+            return result;
+        }
+    }
+    Ok(CompletedDownload {
+        install_path: temp_dir.to_string_lossy().to_string(),
+        reported_size,
+        flattened: None,
+    })
+}
+
+/// Finishes moves that were interrupted by a restart and records their items.
+pub fn resume_moves(app: &AppHandle) {
+    let journals = match load_move_journals(app) {
+        Ok(journals) => journals,
+        Err(e) => {
+            report_problem(app, format!("Failed to load move journals: {}", e));
+            return;
+        }
+    };
+    let journals: Vec<MoveJournal> = journals
+        .into_iter()
+        .filter_map(|journal| journal.map_err(|e| report_problem(app, e)).ok())
+        .collect();
+    if journals.is_empty() {
+        return;
+    }
+    let app = app.clone();
+    thread::spawn(move || {
+        for journal in journals {
+            let _ = app.emit(
+                "terminal-output",
+                format!("Resuming move of {}", journal.item.name),
+            );
+            let result = complete_move(&app, &journal);
+            let keep = result.is_err() && is_resumable(&journal);
+            if let Err(failure) = &result {
+                report_problem(
+                    &app,
+                    format!(
+                        "Could not finish moving {}: {}",
+                        journal.item.name, failure.message
+                    ),
+                );
+            }
+            let elapsed = Duration::from_secs(unix_now().saturating_sub(journal.started_at));
+            record_history(&app, &journal.item, result, elapsed);
+            if !keep {
+                let _ = remove_move_journal(&app, &journal.item.id);
+            }
         }
+    });
+}
+
+// The download is still staged, or a replacement was fully copied but not swapped in.
+fn is_resumable(journal: &MoveJournal) -> bool {
+    let staged = journal.replace
+        && file_manager::replace_siblings(Path::new(&journal.dst))
+            .iter()
+            .any(|p| p.exists());
+    Path::new(&journal.src).exists() || staged
+}
+
+// Moves staged content into its install folder and writes the marker and manifest.
+// Safe to run again after an interruption; what was already copied is kept.
+fn complete_move(
+    app: &AppHandle,
+    journal: &MoveJournal,
+) -> Result<CompletedDownload, DownloadFailure> {
+    let id = journal.item.id.as_str();
+    let src = Path::new(&journal.src);
+    let dst = Path::new(&journal.dst);
+    set_status(app, id, Status::Moving { progress: 0.0 });
+    let _ = app.emit("queue-update", ());
+
+    let mut last_emit: Option<Instant> = None;
+    let mut on_progress = |copied: u64, total: u64| {
+        if copied < total && last_emit.is_some_and(|t| t.elapsed() < MOVE_PROGRESS_INTERVAL) {
+            return;
+        }
+        last_emit = Some(Instant::now());
+        let _ = app.emit(
+            "move-progress",
+            serde_json::json!({ "id": id, "copied": copied, "total": total }),
+        );
+    };
+    let mut move_in = |dir: &Path| {
+        if journal.flattened.is_some() {
+            file_manager::hoist(src, dir, &mut on_progress)
+        } else {
+            file_manager::move_with_progress(src, dir, &mut on_progress)
+        }
+    };
+
+    let retain = journal.snapshot.as_deref().map(Path::new);
+    if src.exists() {
+        if journal.replace {
+            file_manager::replace_dir(dst, retain, move_in).map_err(fs_failure)?;
+        } else {
+            if let Some(parent) = dst.parent() {
+                fs::create_dir_all(parent).map_err(fs_failure)?;
+            }
+            move_in(dst).map_err(fs_failure)?;
+        }
+    } else if journal.replace {
+        // The content was staged before the interruption; the swap may not have run.
+        file_manager::finish_replace(dst, retain).map_err(fs_failure)?;
+    }
+    if !dst.is_dir() {
+        return Err(DownloadFailure::new(
+            FailureClass::FileSystem,
+            format!("{} is missing after the move", dst.display()),
+        ));
+    }
+    if let Some(retain) = retain {
+        unshare_version(app, retain);
+    }
+
+    let _ = fs::remove_dir_all(&journal.temp_dir);
+    if journal.replace {
+        let keep = keep_versions_for(app, &journal.item.steam_id);
+        if let Err(e) = prune_versions(dst, keep) {
+            let _ = app.emit(
+                "terminal-output",
+                format!("Failed to prune old versions: {}", e),
+            );
+        }
+    }
+    let _ = save_marker(
+        dst,
+        &InstallMarker {
+            steam_id: journal.item.steam_id.clone(),
+            name: journal.item.name.clone(),
+        },
+    );
+    if let Err(e) = record_manifest(app, id, dst) {
+        let _ = app.emit(
+            "terminal-output",
+            format!("Failed to write manifest: {}", e),
+        );
+    }
+    if current_settings(app).dedupe {
+        if let Err(e) = dedupe_install(app, dst) {
+            let _ = app.emit("terminal-output", format!("Failed to dedupe: {}", e));
+        }
+    }
+    if journal.replace {
+        collect_garbage_for(app, [dst]);
     }
-    Ok(temp_dir.to_string_lossy().to_string())
+    Ok(CompletedDownload {
+        install_path: journal.dst.clone(),
+        reported_size: journal.reported_size,
+        flattened: journal.flattened.clone(),
+    })
 }
 
-fn parse_ids(steam_id: &str) -> (Option<String>, String) {
-    match steam_id.split_once(':') {
-        Some((g, f)) => (Some(g.to_string()), f.to_string()),
-        None => (None, steam_id.to_string()),
+// An explicit choice on the item wins; otherwise an update keeps the layout of the
+// existing install, falling back to the app profile.
+fn flatten_for(app: &AppHandle, item: &DownloadItem, app_id: &str) -> bool {
+    if let Some(flatten) = item.flatten {
+        return flatten;
     }
+    let previous = app
+        .state::<HistoryState>()
+        .0
+        .lock()
+        .ok()
+        .and_then(|history| {
+            history
+                .iter()
+                .rev()
+                .find(|h| h.steam_id == item.steam_id && matches!(h.outcome, Outcome::Completed))
+                .map(|h| h.flattened.is_some())
+        });
+    previous
+        .or(current_settings(app).profile(app_id).flatten)
+        .unwrap_or(false)
 }
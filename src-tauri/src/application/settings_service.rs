@@ -0,0 +1,201 @@
+use crate::domain::types::{ProcessManagerState, Settings, SettingsState};
+use crate::infrastructure::bin_loader;
+use crate::infrastructure::persistence::{load_settings, save_settings};
+use crate::infrastructure::process_manager::ProcessManager;
+use std::path::{Component, Path, PathBuf};
+use tauri::{AppHandle, Manager};
+
+pub fn init_settings(app: &AppHandle, state: &SettingsState) -> Result<(), String> {
+    let loaded = load_settings(app)?;
+    let mut settings = state.0.lock().map_err(|_| "Failed to lock mutex")?;
+    *settings = loaded;
+    Ok(())
+}
+
+pub fn get_settings(state: &SettingsState) -> Result<Settings, String> {
+    let settings = state.0.lock().map_err(|_| "Failed to lock mutex")?;
+    Ok(settings.clone())
+}
+
+pub fn update_settings(
+    app: &AppHandle,
+    state: &SettingsState,
+    new_settings: Settings,
+) -> Result<(), String> {
+    validate_settings(app, &new_settings)?;
+    let previous = get_settings(state)?.steamcmd_path;
+    if let Some(path) = &new_settings.steamcmd_path {
+        if previous.as_ref() != Some(path) {
+            check_steamcmd(app, path)?;
+        }
+    }
+    let mut settings = state.0.lock().map_err(|_| "Failed to lock mutex")?;
+    save_settings(app, &new_settings)?;
+    *settings = new_settings;
+    Ok(())
+}
+
+pub fn detect_steamcmd() -> Vec<String> {
+    bin_loader::detect_steamcmd()
+        .iter()
+        .map(|p| p.to_string_lossy().to_string())
+        .collect()
+}
+
+/// Runs the given binary to make sure it is steamcmd. It is tracked like a download
+/// so it is stopped if the app closes meanwhile.
+pub fn check_steamcmd(app: &AppHandle, path: &str) -> Result<(), String> {
+    let processes = app
+        .state::<ProcessManagerState>()
+        .0
+        .lock()
+        .map_err(|_| "Failed to lock process manager")?
+        .processes
+        .clone();
+    bin_loader::check_steamcmd(&PathBuf::from(path), &ProcessManager { processes })
+}
+
+fn validate_settings(app: &AppHandle, settings: &Settings) -> Result<(), String> {
+    let roots: Vec<PathBuf> = settings
+        .download_roots
+        .iter()
+        .chain(
+            settings
+                .app_profiles
+                .values()
+                .filter_map(|p| p.root.as_ref()),
+        )
+        .map(PathBuf::from)
+        .collect();
+    let app_data = app.path().app_data_dir().map_err(|e| e.to_string())?;
+    check_roots(&roots, &app_data)?;
+    if let Some(path) = &settings.steamcmd_path {
+        if !Path::new(path).is_absolute() {
+            return Err(format!("steamcmd path must be absolute: {}", path));
+        }
+    }
+    for target in settings
+        .app_profiles
+        .values()
+        .filter_map(|p| p.deploy.as_ref())
+    {
+        if !Path::new(&target.dir).is_absolute() {
+            return Err(format!(
+                "Deploy folder must be an absolute path: {}",
+                target.dir
+            ));
+        }
+    }
+    Ok(())
+}
+
+/// Library roots bound what delete, trash and adopt may touch, so each has to be a
+/// folder of its own: not a filesystem root, not overlapping the app's data (other than
+/// being its default download folder), and not nested in another root.
+fn check_roots(roots: &[PathBuf], app_data: &Path) -> Result<(), String> {
+    let app_data = normalize(app_data);
+    let default_root = app_data.join("download");
+    let mut checked: Vec<PathBuf> = Vec::new();
+    for root in roots {
+        if !root.is_absolute() || root.components().any(|c| c == Component::ParentDir) {
+            return Err(format!(
+                "Download root must be an absolute path: {}",
+                root.display()
+            ));
+        }
+        let root = normalize(root);
+        if root.parent().is_none() {
+            return Err(format!(
+                "A drive or filesystem root cannot be a download root: {}",
+                root.display()
+            ));
+        }
+        if root != default_root && (root.starts_with(&app_data) || app_data.starts_with(&root)) {
+            return Err(format!(
+                "Download root overlaps the app's data folder: {}",
+                root.display()
+            ));
+        }
+        if let Some(other) = checked
+            .iter()
+            .find(|other| **other != root && (root.starts_with(other) || other.starts_with(&root)))
+        {
+            return Err(format!(
+                "Download roots cannot be inside each other: {} and {}",
+                root.display(),
+                other.display()
+            ));
+        }
+        checked.push(root);
+    }
+    Ok(())
+}
+
+// Resolves symlinks where the path exists, so two spellings of a folder compare equal.
+fn normalize(path: &Path) -> PathBuf {
+    path.canonicalize().unwrap_or_else(|_| path.to_path_buf())
+}
+
+/// Snapshot of the current settings, falling back to defaults if unavailable.
+pub fn current_settings(app: &AppHandle) -> Settings {
+    app.try_state::<SettingsState>()
+        .and_then(|state| state.0.lock().ok().map(|s| s.clone()))
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::check_roots;
+    use std::path::{Path, PathBuf};
+
+    fn roots(paths: &[&str]) -> Vec<PathBuf> {
+        paths.iter().map(PathBuf::from).collect()
+    }
+
+    const APP_DATA: &str = "/nonexistent/home/.local/share/steamdl";
+
+    #[test]
+    fn accepts_separate_roots() {
+        let app_data = Path::new(APP_DATA);
+        assert!(check_roots(
+            &roots(&["/nonexistent/games", "/nonexistent/mods"]),
+            app_data
+        )
+        .is_ok());
+        assert!(check_roots(
+            &roots(&["/nonexistent/games", "/nonexistent/games"]),
+            app_data
+        )
+        .is_ok());
+        let default_root = format!("{}/download", APP_DATA);
+        assert!(check_roots(&roots(&[&default_root]), app_data).is_ok());
+    }
+
+    #[test]
+    fn rejects_filesystem_roots_and_relative_paths() {
+        let app_data = Path::new(APP_DATA);
+        assert!(check_roots(&roots(&["/"]), app_data).is_err());
+        assert!(check_roots(&roots(&["games"]), app_data).is_err());
+        assert!(check_roots(&roots(&["/nonexistent/games/../etc"]), app_data).is_err());
+    }
+
+    #[test]
+    fn rejects_roots_overlapping_app_data() {
+        let app_data = Path::new(APP_DATA);
+        assert!(check_roots(&roots(&[APP_DATA]), app_data).is_err());
+        assert!(check_roots(&roots(&["/nonexistent/home"]), app_data).is_err());
+        let inside = format!("{}/trash", APP_DATA);
+        assert!(check_roots(&roots(&[&inside]), app_data).is_err());
+        let in_default = format!("{}/download/games", APP_DATA);
+        assert!(check_roots(&roots(&[&in_default]), app_data).is_err());
+    }
+
+    #[test]
+    fn rejects_nested_roots() {
+        let app_data = Path::new(APP_DATA);
+        let nested = roots(&["/nonexistent/games", "/nonexistent/games/workshop"]);
+        assert!(check_roots(&nested, app_data).is_err());
+        let nested = roots(&["/nonexistent/games/workshop", "/nonexistent/games"]);
+        assert!(check_roots(&nested, app_data).is_err());
+    }
+}
@@ -0,0 +1,35 @@
+use crate::application::janitor_service::scan_temp_folders;
+use crate::domain::types::{StartupReport, StartupState};
+use tauri::{AppHandle, Emitter, Manager};
+
+/// Keeps a problem for the window to show. `startup-report` tells a window that is
+/// already listening to fetch the report again.
+pub fn report_problem(app: &AppHandle, message: String) {
+    if let Some(state) = app.try_state::<StartupState>() {
+        if let Ok(mut report) = state.0.lock() {
+            report.problems.push(message.clone());
+        }
+    }
+    let _ = app.emit("terminal-output", message);
+    let _ = app.emit("startup-report", ());
+}
+
+/// Looks for leftover downloads once history and the queue are loaded.
+pub fn scan_leftovers(app: &AppHandle) {
+    match scan_temp_folders(app) {
+        Ok(folders) => {
+            if let Some(state) = app.try_state::<StartupState>() {
+                if let Ok(mut report) = state.0.lock() {
+                    report.temp_folders = folders;
+                }
+            }
+        }
+        Err(e) => report_problem(app, format!("Failed to scan download folders: {}", e)),
+    }
+}
+
+pub fn get_startup_report(app: &AppHandle) -> StartupReport {
+    app.try_state::<StartupState>()
+        .and_then(|state| state.0.lock().ok().map(|r| r.clone()))
+        .unwrap_or_default()
+}
@@ -0,0 +1,156 @@
+use crate::application::dedupe_service::collect_garbage_for;
+use crate::application::library_service::path_policy;
+use crate::application::version_service::versions_dir;
+use crate::domain::types::{HistoryItem, HistoryState, TrashEntry, TrashState};
+use crate::infrastructure::file_manager;
+use crate::infrastructure::path_policy::PathPolicy;
+use crate::infrastructure::persistence::{load_trash, remove_manifest, save_history, save_trash};
+use std::fs;
+use std::path::{Path, PathBuf};
+use tauri::{AppHandle, Manager};
+
+const SECS_PER_DAY: u64 = 24 * 60 * 60;
+
+pub fn trash_root(app: &AppHandle) -> Result<PathBuf, String> {
+    Ok(app
+        .path()
+        .app_data_dir()
+        .map_err(|e| e.to_string())?
+        .join("trash"))
+}
+
+pub fn init_trash(app: &AppHandle, state: &TrashState, retention_days: u64) -> Result<(), String> {
+    let loaded = load_trash(app)?;
+    let mut trash = state.0.lock().map_err(|_| "Failed to lock mutex")?;
+    *trash = loaded;
+
+    let cutoff = now().saturating_sub(retention_days * SECS_PER_DAY);
+    let (expired, kept): (Vec<_>, Vec<_>) = trash.drain(..).partition(|e| e.trashed_at < cutoff);
+    *trash = kept;
+    if !expired.is_empty() {
+        let root = trash_root(app)?;
+        let policy = path_policy(app)?;
+        for entry in &expired {
+            let _ = remove_entry_dir(&policy, &root, &entry.id);
+            let _ = remove_manifest(app, &entry.item.id);
+        }
+        save_trash(app, &trash)?;
+        collect_garbage_for(app, expired.iter().map(|e| Path::new(&e.item.install_path)));
+    }
+    Ok(())
+}
+
+pub fn get_all_trash(state: &TrashState) -> Result<Vec<TrashEntry>, String> {
+    let trash = state.0.lock().map_err(|_| "Failed to lock mutex")?;
+    Ok(trash.clone())
+}
+
+pub fn move_to_trash(
+    app: &AppHandle,
+    state: &TrashState,
+    item: HistoryItem,
+    keep_files: bool,
+) -> Result<(), String> {
+    let mut trash = state.0.lock().map_err(|_| "Failed to lock mutex")?;
+    let id = uuid::Uuid::new_v4().to_string();
+    let install_path = Path::new(&item.install_path);
+
+    let trash_path = if !keep_files && !item.install_path.is_empty() && install_path.exists() {
+        path_policy(app)?.resolve(install_path)?;
+        let folder_name = install_path.file_name().ok_or("Invalid install path")?;
+        let dest = trash_root(app)?.join(&id).join(folder_name);
+        fs::create_dir_all(dest.parent().ok_or("Invalid trash path")?)
+            .map_err(|e| e.to_string())?;
+        file_manager::move_recursive(install_path, &dest).map_err(|e| e.to_string())?;
+        // Kept versions go with the install so a restore brings them back too.
+        let versions = versions_dir(install_path);
+        if versions.exists() {
+            let _ = file_manager::move_recursive(&versions, &versions_dir(&dest));
+        }
+        Some(dest.to_string_lossy().to_string())
+    } else {
+        None
+    };
+
+    trash.push(TrashEntry {
+        id,
+        item,
+        trash_path,
+        trashed_at: now(),
+    });
+    save_trash(app, &trash)
+}
+
+pub fn restore_item(
+    app: &AppHandle,
+    history_state: &HistoryState,
+    state: &TrashState,
+    id: &str,
+) -> Result<HistoryItem, String> {
+    let mut history = history_state.0.lock().map_err(|_| "Failed to lock mutex")?;
+    let mut trash = state.0.lock().map_err(|_| "Failed to lock mutex")?;
+    let pos = trash
+        .iter()
+        .position(|e| e.id == id)
+        .ok_or("Trash entry not found")?;
+
+    if let Some(trash_path) = &trash[pos].trash_path {
+        let policy = path_policy(app)?;
+        let original = Path::new(&trash[pos].item.install_path);
+        policy.resolve(Path::new(trash_path))?;
+        policy.check_target(original)?;
+        if original.exists() {
+            return Err(format!(
+                "Cannot restore: {} already exists",
+                original.display()
+            ));
+        }
+        if let Some(parent) = original.parent() {
+            fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+        }
+        file_manager::move_recursive(Path::new(trash_path), original).map_err(|e| e.to_string())?;
+        let versions = versions_dir(Path::new(trash_path));
+        if versions.exists() && !versions_dir(original).exists() {
+            let _ = file_manager::move_recursive(&versions, &versions_dir(original));
+        }
+        let _ = remove_entry_dir(&policy, &trash_root(app)?, id);
+    }
+
+    let entry = trash.remove(pos);
+    history.retain(|h| h.id != entry.item.id);
+    history.push(entry.item.clone());
+    save_history(app, &history)?;
+    save_trash(app, &trash)?;
+    Ok(entry.item)
+}
+
+pub fn empty_trash(app: &AppHandle, state: &TrashState) -> Result<(), String> {
+    let mut trash = state.0.lock().map_err(|_| "Failed to lock mutex")?;
+    let root = trash_root(app)?;
+    let policy = path_policy(app)?;
+    for entry in trash.iter() {
+        remove_entry_dir(&policy, &root, &entry.id)?;
+        let _ = remove_manifest(app, &entry.item.id);
+    }
+    let emptied: Vec<TrashEntry> = std::mem::take(&mut *trash);
+    save_trash(app, &trash)?;
+    drop(trash);
+    collect_garbage_for(app, emptied.iter().map(|e| Path::new(&e.item.install_path)));
+    Ok(())
+}
+
+fn remove_entry_dir(policy: &PathPolicy, root: &Path, id: &str) -> Result<(), String> {
+    let dir = root.join(id);
+    if dir.exists() {
+        let dir = policy.resolve(&dir)?;
+        fs::remove_dir_all(&dir).map_err(|e| e.to_string())?;
+    }
+    Ok(())
+}
+
+fn now() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs()
+}
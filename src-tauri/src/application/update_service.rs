@@ -0,0 +1,100 @@
+use crate::domain::steamcmd_script::{AppId, PublishedFileId, SteamTarget};
+use crate::domain::types::{DownloadItem, HistoryState, Outcome};
+use crate::infrastructure::deployer;
+use crate::infrastructure::file_manager;
+use crate::infrastructure::persistence::{load_workshop_state, save_workshop_state};
+use std::fs;
+use std::path::{Path, PathBuf};
+use tauri::{AppHandle, Manager};
+
+/// Prepares `temp_dir` so steamcmd sees an installed workshop item as present and only
+/// fetches what changed: the install is linked back into the workshop content layout
+/// and the `.acf` kept from its last download is restored. Returns whether anything
+/// was staged. App downloads need none of this, as they are updated in place.
+///
+/// steamcmd writes changed files to its downloads folder and moves them over the
+/// staged ones, so hardlinks to the install are replaced rather than written through.
+/// They are only copied where they cannot be linked.
+pub fn stage_existing_install(
+    app: &AppHandle,
+    item: &DownloadItem,
+    app_id: AppId,
+    file_id: PublishedFileId,
+    temp_dir: &Path,
+) -> Result<bool, String> {
+    let workshop = temp_dir.join("steamapps").join("workshop");
+    let content = workshop
+        .join("content")
+        .join(app_id.to_string())
+        .join(file_id.to_string());
+    // Partial data from an interrupted attempt is picked up by steamcmd as it is.
+    if content.exists() {
+        return Ok(false);
+    }
+    let acf = match load_workshop_state(app, app_id, file_id) {
+        Some(acf) => acf,
+        None => return Ok(false),
+    };
+    let (install_dir, flattened) = match installed_copy(app, &item.steam_id) {
+        Some(found) => found,
+        None => return Ok(false),
+    };
+
+    let staged = match flattened.as_deref() {
+        Some(wrapper) if !wrapper.is_empty() => content.join(wrapper),
+        _ => content,
+    };
+    let result = deployer::link_tree(&install_dir, &staged)
+        .and_then(|_| fs::write(workshop.join(format!("appworkshop_{}.acf", app_id)), acf));
+    if let Err(e) = result {
+        let _ = fs::remove_dir_all(&workshop);
+        return Err(e.to_string());
+    }
+    Ok(true)
+}
+
+/// Bytes [`stage_existing_install`] would add to the filesystem of `temp_dir`: none
+/// when the install can be linked there, its full size when it has to be copied.
+pub fn staging_size(app: &AppHandle, item: &DownloadItem, temp_dir: &Path) -> u64 {
+    let Ok(SteamTarget::Workshop { app_id, file_id }) = item.steam_id.parse() else {
+        return 0;
+    };
+    if temp_dir.exists() || load_workshop_state(app, app_id, file_id).is_none() {
+        return 0;
+    }
+    let Some((install_dir, _)) = installed_copy(app, &item.steam_id) else {
+        return 0;
+    };
+    if file_manager::same_filesystem(&install_dir, temp_dir).unwrap_or(false) {
+        return 0;
+    }
+    file_manager::dir_stats(&install_dir)
+        .map(|(bytes, _)| bytes)
+        .unwrap_or(0)
+}
+
+/// Keeps steamcmd's record of a finished workshop download for the next update.
+pub fn keep_workshop_state(
+    app: &AppHandle,
+    app_id: AppId,
+    file_id: PublishedFileId,
+    temp_dir: &Path,
+) -> Result<(), String> {
+    let acf_path = temp_dir
+        .join("steamapps")
+        .join("workshop")
+        .join(format!("appworkshop_{}.acf", app_id));
+    let acf = fs::read(acf_path).map_err(|e| e.to_string())?;
+    save_workshop_state(app, app_id, file_id, &acf)
+}
+
+fn installed_copy(app: &AppHandle, steam_id: &str) -> Option<(PathBuf, Option<String>)> {
+    let state = app.try_state::<HistoryState>()?;
+    let history = state.0.lock().ok()?;
+    history
+        .iter()
+        .rev()
+        .filter(|h| h.steam_id == steam_id && matches!(h.outcome, Outcome::Completed))
+        .map(|h| (PathBuf::from(&h.install_path), h.flattened.clone()))
+        .find(|(dir, _)| !dir.as_os_str().is_empty() && dir.is_dir())
+}
@@ -0,0 +1,94 @@
+use crate::domain::manifest::{compare, Manifest, VerifyReport};
+use crate::domain::types::{HistoryItem, HistoryState, Outcome};
+use crate::infrastructure::hasher::build_manifest;
+use crate::infrastructure::persistence::{load_manifest, save_manifest};
+use std::path::Path;
+use std::thread;
+use tauri::{AppHandle, Emitter};
+
+pub fn record_manifest(app: &AppHandle, id: &str, install_dir: &Path) -> Result<(), String> {
+    let entries = build_manifest(install_dir).map_err(|e| e.to_string())?;
+    let manifest = Manifest {
+        created_at: std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_secs(),
+        entries,
+    };
+    save_manifest(app, id, &manifest)
+}
+
+pub fn verify_item(
+    app: &AppHandle,
+    state: &HistoryState,
+    id: &str,
+) -> Result<VerifyReport, String> {
+    let item = find_item(state, id)?;
+    verify(app, &item)
+}
+
+/// Verifies every completed item on a background thread, reporting through
+/// `verify-progress` and a final `verify-complete` event.
+pub fn verify_library(app: &AppHandle, state: &HistoryState) -> Result<(), String> {
+    let items: Vec<HistoryItem> = state
+        .0
+        .lock()
+        .map_err(|_| "Failed to lock mutex")?
+        .iter()
+        .filter(|h| matches!(h.outcome, Outcome::Completed))
+        .cloned()
+        .collect();
+
+    let app = app.clone();
+    thread::spawn(move || {
+        let total = items.len();
+        let mut reports = Vec::new();
+        for (index, item) in items.iter().enumerate() {
+            let _ = app.emit(
+                "verify-progress",
+                serde_json::json!({
+                    "id": item.id,
+                    "name": item.name,
+                    "current": index + 1,
+                    "total": total
+                }),
+            );
+            match verify(&app, item) {
+                Ok(report) => reports.push(report),
+                Err(e) => {
+                    let _ = app.emit(
+                        "terminal-output",
+                        format!("Verify failed for {}: {}", item.name, e),
+                    );
+                }
+            }
+        }
+        let _ = app.emit("verify-complete", reports);
+    });
+    Ok(())
+}
+
+fn verify(app: &AppHandle, item: &HistoryItem) -> Result<VerifyReport, String> {
+    let manifest = load_manifest(app, &item.id)?.ok_or("No manifest was recorded for this item")?;
+    let dir = Path::new(&item.install_path);
+    if item.install_path.is_empty() || !dir.is_dir() {
+        return Ok(VerifyReport {
+            id: item.id.clone(),
+            missing: manifest.entries.iter().map(|e| e.path.clone()).collect(),
+            ..Default::default()
+        });
+    }
+    let actual = build_manifest(dir).map_err(|e| e.to_string())?;
+    Ok(compare(&item.id, &manifest.entries, &actual))
+}
+
+fn find_item(state: &HistoryState, id: &str) -> Result<HistoryItem, String> {
+    state
+        .0
+        .lock()
+        .map_err(|_| "Failed to lock mutex")?
+        .iter()
+        .find(|h| h.id == id)
+        .cloned()
+        .ok_or_else(|| "History item not found".to_string())
+}
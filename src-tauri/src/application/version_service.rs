@@ -0,0 +1,224 @@
+use crate::application::dedupe_service::{collect_garbage_for, unshare_version};
+use crate::application::deploy_service::refresh_deployments;
+use crate::application::library_service::path_policy;
+use crate::application::settings_service::current_settings;
+use crate::application::verify_service::record_manifest;
+use crate::domain::steamcmd_script::SteamTarget;
+use crate::domain::types::{HistoryItem, HistoryState, InstallMarker, Outcome, VersionSnapshot};
+use crate::infrastructure::file_manager;
+use crate::infrastructure::persistence::{
+    load_version_infos, remove_workshop_state, save_history, save_marker, save_version_info,
+};
+use std::fs;
+use std::path::{Path, PathBuf};
+use tauri::{AppHandle, Emitter, Manager};
+
+/// Previous versions of an install are kept in a hidden sibling folder.
+pub fn versions_dir(install_dir: &Path) -> PathBuf {
+    let name = install_dir
+        .file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .unwrap_or_default();
+    install_dir.with_file_name(format!(".{}.versions", name))
+}
+
+/// The item's own setting wins, then the app profile, then the global default.
+pub fn keep_versions_for(app: &AppHandle, steam_id: &str) -> u32 {
+    let item_setting = latest_entry(app, steam_id).and_then(|h| h.keep_versions);
+    if let Some(keep) = item_setting {
+        return keep;
+    }
+    let settings = current_settings(app);
+    steam_id
+        .parse::<SteamTarget>()
+        .ok()
+        .and_then(|target| settings.profile(&target.app_id().to_string()).keep_versions)
+        .unwrap_or(settings.keep_versions)
+}
+
+/// Describes the current contents of `install_dir` before an update replaces them and
+/// returns the folder they should be moved to, or `None` when no versions are kept.
+pub fn prepare_snapshot(
+    app: &AppHandle,
+    install_dir: &Path,
+    steam_id: &str,
+) -> Result<Option<PathBuf>, String> {
+    if keep_versions_for(app, steam_id) == 0 {
+        return Ok(None);
+    }
+    let current = latest_entry(app, steam_id)
+        .filter(|h| Path::new(&h.install_path) == install_dir)
+        .unwrap_or_default();
+    let (size_bytes, file_count) = file_manager::dir_stats(install_dir).unwrap_or_default();
+    let snapshot = VersionSnapshot {
+        id: uuid::Uuid::new_v4().to_string(),
+        created_at: now(),
+        item_id: current.id,
+        time_updated: current.time_updated,
+        size_bytes,
+        file_count,
+        flattened: current.flattened,
+    };
+    let dir = versions_dir(install_dir);
+    save_version_info(&dir, &snapshot)?;
+    Ok(Some(dir.join(&snapshot.id)))
+}
+
+/// Removes all but the `keep` newest versions, along with records whose folder never
+/// got written.
+pub fn prune_versions(install_dir: &Path, keep: u32) -> Result<(), String> {
+    let dir = versions_dir(install_dir);
+    let mut versions = load_version_infos(&dir);
+    versions.sort_by_key(|v| std::cmp::Reverse(v.created_at));
+    let mut kept = 0;
+    for version in versions {
+        let folder = dir.join(&version.id);
+        if folder.is_dir() && kept < keep {
+            kept += 1;
+            continue;
+        }
+        if folder.exists() {
+            fs::remove_dir_all(&folder).map_err(|e| e.to_string())?;
+        }
+        let _ = fs::remove_file(dir.join(format!("{}.json", version.id)));
+    }
+    if kept == 0 {
+        let _ = fs::remove_dir(&dir);
+    }
+    Ok(())
+}
+
+pub fn list_versions(state: &HistoryState, id: &str) -> Result<Vec<VersionSnapshot>, String> {
+    let history = state.0.lock().map_err(|_| "Failed to lock mutex")?;
+    let item = history
+        .iter()
+        .find(|h| h.id == id)
+        .ok_or("History item not found")?;
+    if item.install_path.is_empty() {
+        return Ok(Vec::new());
+    }
+    let dir = versions_dir(Path::new(&item.install_path));
+    let mut versions: Vec<_> = load_version_infos(&dir)
+        .into_iter()
+        .filter(|v| dir.join(&v.id).is_dir())
+        .collect();
+    versions.sort_by_key(|v| std::cmp::Reverse(v.created_at));
+    Ok(versions)
+}
+
+/// Swaps a kept version back into the install folder. The contents it replaces are
+/// kept as a version in turn, so a rollback can itself be undone.
+pub fn rollback_item(
+    app: &AppHandle,
+    state: &HistoryState,
+    id: &str,
+    version_id: &str,
+) -> Result<HistoryItem, String> {
+    let mut history = state.0.lock().map_err(|_| "Failed to lock mutex")?;
+    let pos = history
+        .iter()
+        .position(|h| h.id == id)
+        .ok_or("History item not found")?;
+    if !matches!(history[pos].outcome, Outcome::Completed) {
+        return Err("Only completed downloads can be rolled back".to_string());
+    }
+    let policy = path_policy(app)?;
+    let install_dir = policy.resolve(Path::new(&history[pos].install_path))?;
+    let dir = versions_dir(&install_dir);
+    let target = load_version_infos(&dir)
+        .into_iter()
+        .find(|v| v.id == version_id)
+        .ok_or("Version not found")?;
+    let target_dir = policy.resolve(&dir.join(&target.id))?;
+
+    let item = &history[pos];
+    let (size_bytes, file_count) = file_manager::dir_stats(&install_dir).unwrap_or_default();
+    let current = VersionSnapshot {
+        id: uuid::Uuid::new_v4().to_string(),
+        created_at: now(),
+        item_id: item.id.clone(),
+        time_updated: item.time_updated,
+        size_bytes,
+        file_count,
+        flattened: item.flattened.clone(),
+    };
+    save_version_info(&dir, &current)?;
+    file_manager::replace_dir(&install_dir, Some(&dir.join(&current.id)), |staged| {
+        fs::rename(&target_dir, staged)
+    })
+    .map_err(|e| e.to_string())?;
+    let _ = fs::remove_file(dir.join(format!("{}.json", target.id)));
+
+    let item = &mut history[pos];
+    item.time_updated = target.time_updated;
+    item.size_bytes = target.size_bytes;
+    item.file_count = target.file_count;
+    item.flattened = target.flattened;
+    let item = item.clone();
+    save_history(app, &history)?;
+    drop(history);
+    unshare_version(app, &dir.join(&current.id));
+
+    let _ = save_marker(
+        &install_dir,
+        &InstallMarker {
+            steam_id: item.steam_id.clone(),
+            name: item.name.clone(),
+        },
+    );
+    // The saved steamcmd state describes the newest download; staged against the
+    // rolled-back files it would make the next update a no-op.
+    if let Ok(SteamTarget::Workshop { app_id, file_id }) = item.steam_id.parse() {
+        remove_workshop_state(app, app_id, file_id)?;
+    }
+    record_manifest(app, &item.id, &install_dir)?;
+    let keep = keep_versions_for(app, &item.steam_id).max(1);
+    prune_versions(&install_dir, keep)?;
+    collect_garbage_for(app, [install_dir.as_path()]);
+
+    // Copy and hardlink deployments still hold the files that were rolled back.
+    if item.deployments.is_empty() {
+        return Ok(item);
+    }
+    if let Err(e) = refresh_deployments(app, state, &item.id) {
+        let _ = app.emit("terminal-output", format!("Redeploy failed: {}", e));
+    }
+    let history = state.0.lock().map_err(|_| "Failed to lock mutex")?;
+    Ok(history
+        .iter()
+        .find(|h| h.id == item.id)
+        .cloned()
+        .unwrap_or(item))
+}
+
+pub fn set_keep_versions(
+    app: &AppHandle,
+    state: &HistoryState,
+    id: &str,
+    keep: Option<u32>,
+) -> Result<(), String> {
+    let mut history = state.0.lock().map_err(|_| "Failed to lock mutex")?;
+    let item = history
+        .iter_mut()
+        .find(|h| h.id == id)
+        .ok_or("History item not found")?;
+    item.keep_versions = keep;
+    save_history(app, &history)
+}
+
+fn latest_entry(app: &AppHandle, steam_id: &str) -> Option<HistoryItem> {
+    let state = app.state::<HistoryState>();
+    let history = state.0.lock().ok()?;
+    history
+        .iter()
+        .rev()
+        .find(|h| h.steam_id == steam_id && matches!(h.outcome, Outcome::Completed))
+        .cloned()
+}
+
+fn now() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs()
+}
@@ -1,3 +1,9 @@
+pub mod gma;
+pub mod library;
+pub mod manifest;
 pub mod parser;
 pub mod sanitizer;
+pub mod steamcmd_output;
+pub mod steamcmd_script;
+pub mod template;
 pub mod types;
@@ -0,0 +1,297 @@
+use serde::{Deserialize, Serialize};
+use std::io::{self, Read, Write};
+
+const MAGIC: &[u8; 4] = b"GMAD";
+const MAX_VERSION: u8 = 3;
+const MAX_STRING_LEN: usize = 64 * 1024;
+
+/// A Garry's Mod addon archive as written by gmad: `GMAD`, a version byte, steam id
+/// and timestamp, required content (version > 1), name, description, author and
+/// addon version, then the file table and the file data in table order. Strings are
+/// NUL-terminated, numbers little-endian.
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct GmaHeader {
+    pub version: u8,
+    /// Sent as a string; 64-bit ids do not survive a JavaScript number.
+    #[serde(with = "string_id")]
+    pub steam_id: u64,
+    pub timestamp: u64,
+    pub required_content: Vec<String>,
+    pub name: String,
+    pub description: String,
+    /// From the JSON gmad stores in the description, when present.
+    pub addon_type: Option<String>,
+    pub tags: Vec<String>,
+    pub author: String,
+    pub addon_version: i32,
+    pub files: Vec<GmaEntry>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GmaEntry {
+    /// Relative, `/`-separated path inside the addon.
+    pub path: String,
+    pub size: u64,
+    pub crc: u32,
+}
+
+#[derive(Deserialize)]
+struct DescriptionJson {
+    description: Option<String>,
+    #[serde(rename = "type")]
+    addon_type: Option<String>,
+    #[serde(default)]
+    tags: Vec<String>,
+}
+
+mod string_id {
+    use serde::{de::Error, Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(id: &u64, s: S) -> Result<S::Ok, S::Error> {
+        s.collect_str(id)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(d: D) -> Result<u64, D::Error> {
+        String::deserialize(d)?.parse().map_err(D::Error::custom)
+    }
+}
+
+/// Reads everything up to the file data; `r` is left at the first file's contents.
+pub fn read_header(r: &mut impl Read) -> Result<GmaHeader, String> {
+    let mut magic = [0u8; 4];
+    r.read_exact(&mut magic).map_err(read_error)?;
+    if &magic != MAGIC {
+        return Err("Not a GMA file".to_string());
+    }
+    let version = read_u8(r)?;
+    if version > MAX_VERSION {
+        return Err(format!("Unsupported GMA version {}", version));
+    }
+    let steam_id = read_u64(r)?;
+    let timestamp = read_u64(r)?;
+
+    let mut required_content = Vec::new();
+    if version > 1 {
+        loop {
+            let content = read_string(r)?;
+            if content.is_empty() {
+                break;
+            }
+            required_content.push(content);
+        }
+    }
+
+    let name = read_string(r)?;
+    let raw_description = read_string(r)?;
+    let author = read_string(r)?;
+    let addon_version = read_u32(r)? as i32;
+    let (description, addon_type, tags) =
+        match serde_json::from_str::<DescriptionJson>(&raw_description) {
+            Ok(json) => (
+                json.description.unwrap_or_default(),
+                json.addon_type,
+                json.tags,
+            ),
+            Err(_) => (raw_description, None, Vec::new()),
+        };
+
+    let mut files = Vec::new();
+    loop {
+        if read_u32(r)? == 0 {
+            break;
+        }
+        let path = read_string(r)?;
+        let size = read_u64(r)?;
+        let crc = read_u32(r)?;
+        files.push(GmaEntry { path, size, crc });
+    }
+
+    Ok(GmaHeader {
+        version,
+        steam_id,
+        timestamp,
+        required_content,
+        name,
+        description,
+        addon_type,
+        tags,
+        author,
+        addon_version,
+        files,
+    })
+}
+
+/// Copies the next file's data from `r` to `out` and checks it against the CRC from
+/// the file table. Files must be read in table order.
+pub fn copy_entry(r: &mut impl Read, entry: &GmaEntry, out: &mut impl Write) -> Result<(), String> {
+    let mut hasher = crc32fast::Hasher::new();
+    let mut remaining = entry.size;
+    let mut buf = vec![0u8; 1 << 16];
+    while remaining > 0 {
+        let want = remaining.min(buf.len() as u64) as usize;
+        r.read_exact(&mut buf[..want]).map_err(read_error)?;
+        hasher.update(&buf[..want]);
+        out.write_all(&buf[..want]).map_err(|e| e.to_string())?;
+        remaining -= want as u64;
+    }
+    let crc = hasher.finalize();
+    if entry.crc != 0 && crc != entry.crc {
+        return Err(format!(
+            "CRC mismatch for {}: expected {:08x}, got {:08x}",
+            entry.path, entry.crc, crc
+        ));
+    }
+    Ok(())
+}
+
+fn read_error(e: io::Error) -> String {
+    if e.kind() == io::ErrorKind::UnexpectedEof {
+        "GMA file is truncated".to_string()
+    } else {
+        e.to_string()
+    }
+}
+
+fn read_u8(r: &mut impl Read) -> Result<u8, String> {
+    let mut buf = [0u8; 1];
+    r.read_exact(&mut buf).map_err(read_error)?;
+    Ok(buf[0])
+}
+
+fn read_u32(r: &mut impl Read) -> Result<u32, String> {
+    let mut buf = [0u8; 4];
+    r.read_exact(&mut buf).map_err(read_error)?;
+    Ok(u32::from_le_bytes(buf))
+}
+
+fn read_u64(r: &mut impl Read) -> Result<u64, String> {
+    let mut buf = [0u8; 8];
+    r.read_exact(&mut buf).map_err(read_error)?;
+    Ok(u64::from_le_bytes(buf))
+}
+
+fn read_string(r: &mut impl Read) -> Result<String, String> {
+    let mut bytes = Vec::new();
+    loop {
+        let byte = read_u8(r)?;
+        if byte == 0 {
+            break;
+        }
+        if bytes.len() >= MAX_STRING_LEN {
+            return Err("GMA string is too long".to_string());
+        }
+        bytes.push(byte);
+    }
+    Ok(String::from_utf8_lossy(&bytes).into_owned())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn push_string(out: &mut Vec<u8>, s: &str) {
+        out.extend_from_slice(s.as_bytes());
+        out.push(0);
+    }
+
+    fn sample_gma(data: &[u8]) -> Vec<u8> {
+        let mut out = MAGIC.to_vec();
+        out.push(3);
+        out.extend_from_slice(&76561197960287930u64.to_le_bytes());
+        out.extend_from_slice(&1_400_000_000u64.to_le_bytes());
+        push_string(&mut out, "");
+        push_string(&mut out, "Test Addon");
+        push_string(
+            &mut out,
+            r#"{"description":"A map","type":"map","tags":["fun"]}"#,
+        );
+        push_string(&mut out, "Author Name");
+        out.extend_from_slice(&1u32.to_le_bytes());
+        out.extend_from_slice(&1u32.to_le_bytes());
+        push_string(&mut out, "maps/test.bsp");
+        out.extend_from_slice(&(data.len() as u64).to_le_bytes());
+        out.extend_from_slice(&crc32fast::hash(data).to_le_bytes());
+        out.extend_from_slice(&0u32.to_le_bytes());
+        out.extend_from_slice(data);
+        out
+    }
+
+    #[test]
+    fn reads_a_valid_addon() {
+        let bytes = sample_gma(b"BSP data");
+        let mut r = &bytes[..];
+        let header = read_header(&mut r).unwrap();
+        assert_eq!(header.version, 3);
+        assert_eq!(header.steam_id, 76561197960287930);
+        assert_eq!(header.name, "Test Addon");
+        assert_eq!(header.description, "A map");
+        assert_eq!(header.addon_type.as_deref(), Some("map"));
+        assert_eq!(header.tags, ["fun"]);
+        assert_eq!(header.author, "Author Name");
+        assert_eq!(header.files.len(), 1);
+        assert_eq!(header.files[0].path, "maps/test.bsp");
+
+        let mut out = Vec::new();
+        copy_entry(&mut r, &header.files[0], &mut out).unwrap();
+        assert_eq!(out, b"BSP data");
+    }
+
+    #[test]
+    fn rejects_truncated_input() {
+        let bytes = sample_gma(b"BSP data");
+        for len in [3, 20, 40] {
+            assert_eq!(
+                read_header(&mut &bytes[..len]).unwrap_err(),
+                "GMA file is truncated"
+            );
+        }
+        let mut r = &bytes[..bytes.len() - 2];
+        let header = read_header(&mut r).unwrap();
+        assert_eq!(
+            copy_entry(&mut r, &header.files[0], &mut Vec::new()).unwrap_err(),
+            "GMA file is truncated"
+        );
+    }
+
+    #[test]
+    fn rejects_oversized_strings() {
+        let mut bytes = MAGIC.to_vec();
+        bytes.push(1);
+        bytes.extend_from_slice(&[0u8; 16]);
+        bytes.extend(std::iter::repeat_n(b'a', MAX_STRING_LEN + 1));
+        bytes.push(0);
+        assert_eq!(
+            read_header(&mut &bytes[..]).unwrap_err(),
+            "GMA string is too long"
+        );
+    }
+
+    #[test]
+    fn rejects_bad_magic_version_and_crc() {
+        let mut bytes = sample_gma(b"BSP data");
+        assert!(read_header(&mut &b"GMAX"[..]).is_err());
+        bytes[4] = MAX_VERSION + 1;
+        assert!(read_header(&mut &bytes[..]).is_err());
+
+        let mut bytes = sample_gma(b"BSP data");
+        let last = bytes.len() - 1;
+        bytes[last] ^= 0xff;
+        let mut r = &bytes[..];
+        let header = read_header(&mut r).unwrap();
+        assert!(copy_entry(&mut r, &header.files[0], &mut Vec::new())
+            .unwrap_err()
+            .starts_with("CRC mismatch"));
+    }
+
+    #[test]
+    fn steam_id_is_serialized_as_a_string() {
+        let header = GmaHeader {
+            steam_id: 76561197960287930,
+            ..Default::default()
+        };
+        let json = serde_json::to_value(&header).unwrap();
+        assert_eq!(json["steam_id"], "76561197960287930");
+        let back: GmaHeader = serde_json::from_value(json).unwrap();
+        assert_eq!(back.steam_id, 76561197960287930);
+    }
+}
@@ -0,0 +1,105 @@
+use crate::domain::types::{HistoryItem, InstallMarker, LibraryReport, OrphanFolder, Outcome};
+use std::collections::HashSet;
+use std::path::Path;
+
+pub struct ScannedFolder {
+    pub path: String,
+    pub folder_name: String,
+    pub marker: Option<InstallMarker>,
+    pub size_bytes: u64,
+}
+
+pub fn reconcile(
+    history: &[HistoryItem],
+    folders: Vec<ScannedFolder>,
+    path_exists: impl Fn(&str) -> bool,
+) -> LibraryReport {
+    let known: HashSet<&Path> = history
+        .iter()
+        .filter(|h| !h.install_path.is_empty())
+        .map(|h| Path::new(&h.install_path))
+        .collect();
+
+    let missing = history
+        .iter()
+        .filter(|h| matches!(h.outcome, Outcome::Completed))
+        .filter(|h| !path_exists(&h.install_path))
+        .map(|h| h.id.clone())
+        .collect();
+
+    let orphans = folders
+        .into_iter()
+        .filter(|f| !known.contains(Path::new(&f.path)))
+        .map(|f| {
+            // A folder name only carries the file id; the app id has to come from the
+            // marker or be looked up when the folder is adopted.
+            let file_id = file_id_from_folder_name(&f.folder_name);
+            OrphanFolder {
+                steam_id: f.marker.as_ref().map(|m| m.steam_id.clone()),
+                name: f.marker.map(|m| m.name),
+                file_id,
+                path: f.path,
+                folder_name: f.folder_name,
+                size_bytes: f.size_bytes,
+            }
+        })
+        .collect();
+
+    LibraryReport { missing, orphans }
+}
+
+/// Accepts a bare workshop file id (`123456`) or a trailing `[123456]` tag.
+pub fn file_id_from_folder_name(name: &str) -> Option<String> {
+    let is_id = |s: &str| !s.is_empty() && s.chars().all(|c| c.is_ascii_digit());
+    let name = name.trim();
+    if is_id(name) {
+        return Some(name.to_string());
+    }
+    let tagged = name.strip_suffix(']')?;
+    let id = &tagged[tagged.rfind('[')? + 1..];
+    is_id(id).then(|| id.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn folder(name: &str, marker: Option<InstallMarker>) -> ScannedFolder {
+        ScannedFolder {
+            path: format!("/lib/{}", name),
+            folder_name: name.to_string(),
+            marker,
+            size_bytes: 0,
+        }
+    }
+
+    #[test]
+    fn file_id_from_folder_name_reads_bare_and_tagged_ids() {
+        assert_eq!(
+            file_id_from_folder_name("123456").as_deref(),
+            Some("123456")
+        );
+        assert_eq!(
+            file_id_from_folder_name("Map Pack [123456]").as_deref(),
+            Some("123456")
+        );
+        assert_eq!(file_id_from_folder_name("Map Pack"), None);
+        assert_eq!(file_id_from_folder_name("Map Pack [12a]"), None);
+    }
+
+    #[test]
+    fn orphan_steam_id_only_comes_from_the_marker() {
+        let marker = InstallMarker {
+            steam_id: "4000:123456".to_string(),
+            name: "Map Pack".to_string(),
+        };
+        let report = reconcile(
+            &[],
+            vec![folder("123456", None), folder("Map Pack", Some(marker))],
+            |_| true,
+        );
+        assert_eq!(report.orphans[0].steam_id, None);
+        assert_eq!(report.orphans[0].file_id.as_deref(), Some("123456"));
+        assert_eq!(report.orphans[1].steam_id.as_deref(), Some("4000:123456"));
+    }
+}
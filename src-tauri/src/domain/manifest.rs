@@ -0,0 +1,51 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ManifestEntry {
+    /// Relative to the install folder, always `/`-separated.
+    pub path: String,
+    pub size: u64,
+    pub sha256: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct Manifest {
+    pub created_at: u64,
+    pub entries: Vec<ManifestEntry>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct VerifyReport {
+    pub id: String,
+    pub missing: Vec<String>,
+    pub modified: Vec<String>,
+    pub extra: Vec<String>,
+}
+
+pub fn compare(id: &str, expected: &[ManifestEntry], actual: &[ManifestEntry]) -> VerifyReport {
+    let actual_by_path: HashMap<&str, &ManifestEntry> =
+        actual.iter().map(|e| (e.path.as_str(), e)).collect();
+    let expected_by_path: HashMap<&str, &ManifestEntry> =
+        expected.iter().map(|e| (e.path.as_str(), e)).collect();
+
+    let mut report = VerifyReport {
+        id: id.to_string(),
+        ..Default::default()
+    };
+    for entry in expected {
+        match actual_by_path.get(entry.path.as_str()) {
+            None => report.missing.push(entry.path.clone()),
+            Some(found) if found.size != entry.size || found.sha256 != entry.sha256 => {
+                report.modified.push(entry.path.clone())
+            }
+            Some(_) => {}
+        }
+    }
+    report.extra = actual
+        .iter()
+        .filter(|e| !expected_by_path.contains_key(e.path.as_str()))
+        .map(|e| e.path.clone())
+        .collect();
+    report
+}
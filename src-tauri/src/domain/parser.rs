@@ -1,15 +1,54 @@
 pub struct WorkshopMetadata {
     pub app_id: String,
+    pub app_name: Option<String>,
     pub title: String,
     pub file_id: String,
 }
 
+pub struct WorkshopDetails {
+    pub app_id: String,
+    pub title: String,
+    pub file_size: Option<u64>,
+    pub time_updated: Option<u64>,
+}
+
+/// Parses an `ISteamRemoteStorage/GetPublishedFileDetails` response for a single item.
+pub fn parse_published_file_details(json: &str) -> Result<WorkshopDetails, String> {
+    let value: serde_json::Value = serde_json::from_str(json).map_err(|e| e.to_string())?;
+    let details = value
+        .pointer("/response/publishedfiledetails/0")
+        .ok_or_else(|| "Missing item details".to_string())?;
+    if details.get("result").and_then(|r| r.as_u64()) != Some(1) {
+        return Err("Workshop item not found".to_string());
+    }
+
+    let as_u64 = |key: &str| {
+        details.get(key).and_then(|v| {
+            v.as_u64()
+                .or_else(|| v.as_str().and_then(|s| s.parse().ok()))
+        })
+    };
+    Ok(WorkshopDetails {
+        app_id: as_u64("consumer_app_id")
+            .ok_or_else(|| "Failed to extract AppID".to_string())?
+            .to_string(),
+        title: details
+            .get("title")
+            .and_then(|t| t.as_str())
+            .unwrap_or_default()
+            .to_string(),
+        file_size: as_u64("file_size"),
+        time_updated: as_u64("time_updated"),
+    })
+}
+
 pub fn parse_workshop_html(html: &str, file_id: &str) -> Result<WorkshopMetadata, String> {
     let app_id = extract_appid(html).ok_or_else(|| "Failed to extract AppID".to_string())?;
     let title = extract_title(html).ok_or_else(|| "Failed to extract Title".to_string())?;
 
     Ok(WorkshopMetadata {
         app_id,
+        app_name: extract_app_name(html),
         title,
         file_id: file_id.to_string(),
     })
@@ -37,16 +76,63 @@ fn extract_appid(html: &str) -> Option<String> {
 }
 
 fn extract_title(html: &str) -> Option<String> {
-    html.find("class=\"workshopItemTitle\"").and_then(|start| {
+    extract_div_text(html, "class=\"workshopItemTitle\"")
+}
+
+fn extract_app_name(html: &str) -> Option<String> {
+    extract_div_text(html, "class=\"apphub_AppName").filter(|s| !s.is_empty())
+}
+
+fn extract_div_text(html: &str, marker: &str) -> Option<String> {
+    html.find(marker).and_then(|start| {
         html.get(start..).and_then(|rest| {
             rest.find('>').and_then(|gt_start| {
                 let content_start = gt_start + 1;
                 rest.get(content_start..).and_then(|content| {
                     content
                         .find("</div>")
-                        .map(|end| content[..end].trim().to_string())
+                        .map(|end| decode_entities(content[..end].trim()))
                 })
             })
         })
     })
 }
+
+// Page text is HTML-escaped; titles are kept as published.
+fn decode_entities(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(start) = rest.find('&') {
+        out.push_str(&rest[..start]);
+        rest = &rest[start..];
+        let decoded = rest.find(';').and_then(|end| {
+            let entity = &rest[1..end];
+            let c = match entity {
+                "amp" => Some('&'),
+                "lt" => Some('<'),
+                "gt" => Some('>'),
+                "quot" => Some('"'),
+                "apos" => Some('\''),
+                _ => entity
+                    .strip_prefix("#x")
+                    .or_else(|| entity.strip_prefix("#X"))
+                    .and_then(|hex| u32::from_str_radix(hex, 16).ok())
+                    .or_else(|| entity.strip_prefix('#').and_then(|dec| dec.parse().ok()))
+                    .and_then(char::from_u32),
+            }?;
+            Some((c, end + 1))
+        });
+        match decoded {
+            Some((c, len)) => {
+                out.push(c);
+                rest = &rest[len..];
+            }
+            None => {
+                out.push('&');
+                rest = &rest[1..];
+            }
+        }
+    }
+    out.push_str(rest);
+    out
+}
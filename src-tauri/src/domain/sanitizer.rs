@@ -1,17 +1,96 @@
+/// Longest folder name we produce, in UTF-8 bytes. Most filesystems allow 255.
+const MAX_NAME_BYTES: usize = 200;
+
+const RESERVED_NAMES: &[&str] = &[
+    "CON", "PRN", "AUX", "NUL", "COM1", "COM2", "COM3", "COM4", "COM5", "COM6", "COM7", "COM8",
+    "COM9", "LPT1", "LPT2", "LPT3", "LPT4", "LPT5", "LPT6", "LPT7", "LPT8", "LPT9",
+];
+
+/// Turns a title into a single folder name that is valid on Windows, macOS and Linux.
+/// Unicode is kept; separators, characters Windows rejects and control characters
+/// become `_`, leading dots (hidden folders, `..`) and trailing dots and spaces are
+/// dropped and reserved device names are prefixed.
 pub fn sanitize_name(name: &str) -> String {
     let sanitized: String = name
         .chars()
         .map(|c| match c {
-            'a'..='z' | 'A'..='Z' | '0'..='9' | '-' | '_' | ' ' | '.' => c,
-            _ => '_',
+            '/' | '\\' | '<' | '>' | ':' | '"' | '|' | '?' | '*' => '_',
+            c if c.is_whitespace() => ' ',
+            c if c.is_control() => '_',
+            c => c,
         })
         .collect();
 
     let collapsed = sanitized.split_whitespace().collect::<Vec<_>>().join(" ");
+    let mut name = truncate_bytes(&collapsed, MAX_NAME_BYTES)
+        .trim_matches(['.', ' '])
+        .to_string();
 
-    if collapsed.is_empty() {
+    let stem = name.split('.').next().unwrap_or("").trim_end();
+    if RESERVED_NAMES.iter().any(|r| r.eq_ignore_ascii_case(stem)) {
+        name.insert(0, '_');
+    }
+
+    if name.is_empty() {
         "unnamed_item".to_string()
     } else {
-        collapsed.chars().take(200).collect()
+        name
+    }
+}
+
+fn truncate_bytes(s: &str, max: usize) -> &str {
+    if s.len() <= max {
+        return s;
+    }
+    let mut end = max;
+    while !s.is_char_boundary(end) {
+        end -= 1;
+    }
+    &s[..end]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn keeps_unicode_titles() {
+        assert_eq!(sanitize_name("東方 マップ"), "東方 マップ");
+        assert_eq!(sanitize_name("Карта — Ёлка"), "Карта — Ёлка");
+        assert_eq!(sanitize_name("Café  déjà\tvu"), "Café déjà vu");
+    }
+
+    #[test]
+    fn replaces_separators_and_reserved_characters() {
+        assert_eq!(sanitize_name("a/b\\c"), "a_b_c");
+        assert_eq!(
+            sanitize_name("What? <Yes>: \"no\" | *"),
+            "What_ _Yes__ _no_ _ _"
+        );
+        assert_eq!(sanitize_name("bell\u{7}"), "bell_");
+    }
+
+    #[test]
+    fn strips_leading_and_trailing_dots() {
+        assert_eq!(sanitize_name(".hidden"), "hidden");
+        assert_eq!(sanitize_name("..."), "unnamed_item");
+        assert_eq!(sanitize_name(".."), "unnamed_item");
+        assert_eq!(sanitize_name(". config"), "config");
+        assert_eq!(sanitize_name("Map v1.0. "), "Map v1.0");
+    }
+
+    #[test]
+    fn prefixes_reserved_device_names() {
+        assert_eq!(sanitize_name("CON"), "_CON");
+        assert_eq!(sanitize_name("nul.txt"), "_nul.txt");
+        assert_eq!(sanitize_name("Console"), "Console");
+    }
+
+    #[test]
+    fn limits_names_by_encoded_bytes() {
+        let name = sanitize_name(&"é".repeat(150));
+        assert!(name.len() <= MAX_NAME_BYTES);
+        assert_eq!(name, "é".repeat(MAX_NAME_BYTES / 2));
+        assert_eq!(sanitize_name(""), "unnamed_item");
     }
 }
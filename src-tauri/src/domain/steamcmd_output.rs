@@ -0,0 +1,264 @@
+use crate::domain::types::{DownloadFailure, EngineState, FailureClass};
+
+#[derive(Debug, Clone, Default)]
+pub struct OutputSummary {
+    pub reported_size: Option<u64>,
+    pub failure: Option<DownloadFailure>,
+    /// steamcmd got through updating itself and loading the Steam API.
+    pub started: bool,
+    pub output_seen: bool,
+    /// It could not update itself, usually for lack of a connection.
+    pub update_failed: bool,
+}
+
+/// How a steamcmd run went as far as the engine itself is concerned.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EngineStart {
+    Started,
+    /// Nothing is wrong with the engine; it could not reach Steam to update.
+    UpdateFailed,
+    /// Exited early for a reason that was not recognised.
+    Stopped,
+    /// Crashed, or exited without printing anything. Repeated, the engine is rebuilt.
+    Broken,
+}
+
+impl OutputSummary {
+    pub fn observe(&mut self, line: &str) {
+        let line = line.trim();
+        self.output_seen |= !line.is_empty();
+        if !self.started && is_startup_done(line) {
+            self.started = true;
+        }
+        if !self.started && is_update_failure(line) {
+            self.update_failed = true;
+        }
+
+        if let Some(size) = parse_downloaded_bytes(line) {
+            self.reported_size = Some(size);
+        } else if let Some(total) = parse_progress_total(line) {
+            self.reported_size = Some(self.reported_size.map_or(total, |s| s.max(total)));
+        }
+
+        if self.failure.is_none() {
+            if let Some(class) = classify_error(line) {
+                self.failure = Some(DownloadFailure::new(class, line));
+            }
+        }
+    }
+
+    pub fn engine_start(&self, crashed: bool) -> EngineStart {
+        if self.started {
+            EngineStart::Started
+        } else if self.update_failed {
+            EngineStart::UpdateFailed
+        } else if crashed || !self.output_seen {
+            EngineStart::Broken
+        } else {
+            EngineStart::Stopped
+        }
+    }
+}
+
+// `[----] !!! Fatal Error: Steamcmd needs to be online to update. Please confirm your
+// network connection and try again.`
+fn is_update_failure(line: &str) -> bool {
+    let lower = line.to_ascii_lowercase();
+    lower.contains("needs to be online to update")
+        || lower.contains("failed to download update")
+        || lower.contains("update failed")
+}
+
+/// steamcmd's own startup: `[ 45%] Downloading update (1,024 of 2,048 KB)...` while
+/// it updates itself, `[----] Verifying installation...`, then the Steam API loading.
+pub fn engine_state(line: &str) -> Option<EngineState> {
+    let line = line.trim();
+    if is_startup_done(line) {
+        return Some(EngineState::Ready);
+    }
+    let (tag, _) = line.strip_prefix('[')?.split_once(']')?;
+    let tag = tag.trim();
+    if tag == "----" {
+        return Some(EngineState::Updating { progress: None });
+    }
+    let progress = tag.strip_suffix('%')?.trim().parse().ok()?;
+    Some(EngineState::Updating {
+        progress: Some(progress),
+    })
+}
+
+/// What steamcmd waits for during an account login. It prints these without a line
+/// break, so they have to be matched on partial output.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LoginPrompt {
+    Password,
+    /// Sent by email.
+    GuardCode,
+    /// From the Steam mobile authenticator.
+    TwoFactorCode,
+}
+
+pub fn login_prompt(text: &str) -> Option<LoginPrompt> {
+    let lower = text.trim().to_ascii_lowercase();
+    if lower.ends_with("password:") {
+        Some(LoginPrompt::Password)
+    } else if lower.ends_with("steam guard code:") {
+        Some(LoginPrompt::GuardCode)
+    } else if lower.ends_with("two-factor code:") {
+        Some(LoginPrompt::TwoFactorCode)
+    } else {
+        None
+    }
+}
+
+// `Waiting for user info...OK`, the last step of a login.
+pub fn is_logged_in(line: &str) -> bool {
+    let line = line.trim();
+    line.starts_with("Waiting for user info") && line.ends_with("OK")
+}
+
+fn is_startup_done(line: &str) -> bool {
+    (line.starts_with("Loading Steam API") && line.ends_with("OK"))
+        || line.starts_with("Logging in")
+        || line.starts_with("Connecting anonymously")
+}
+
+/// Recognises steamcmd's own error lines, e.g.
+/// `ERROR! Download item 123 failed (Access Denied).`,
+/// `Error! App '740' state is 0x202 after update job.` and
+/// `Logging in user 'name' to Steam Public...FAILED (Invalid Password)`. Anything else,
+/// including workshop content that merely mentions a failure, is not an error.
+pub fn classify_error(line: &str) -> Option<FailureClass> {
+    let line = line.trim();
+    let is_login = line.starts_with("Logging in")
+        || line.starts_with("Waiting for user info")
+        || line.starts_with("FAILED login")
+        || line.starts_with("Login Failure:");
+    let is_error = line
+        .get(..6)
+        .is_some_and(|p| p.eq_ignore_ascii_case("error!"))
+        || (line.contains("FAILED (") && line.trim_end_matches('.').ends_with(')'))
+        || line.starts_with("FAILED login")
+        || line.starts_with("Login Failure:");
+    if !is_error {
+        return None;
+    }
+
+    let lower = line.to_ascii_lowercase();
+    let class = if lower.contains("disk write failure")
+        || lower.contains("disk space")
+        || lower.contains("state is 0x202")
+    {
+        FailureClass::DiskFull
+    } else if lower.contains("access denied") || lower.contains("no subscription") {
+        FailureClass::AccessDenied
+    } else if lower.contains("timeout") || lower.contains("timed out") {
+        FailureClass::Timeout
+    } else if is_login
+        || lower.contains("invalid password")
+        || lower.contains("account logon denied")
+        || lower.contains("two-factor")
+        || lower.contains("rate limit")
+        || lower.contains("not logged on")
+    {
+        FailureClass::Login
+    } else {
+        FailureClass::Download
+    };
+    Some(class)
+}
+
+// `Success. Downloaded item 123 to "..." (4567 bytes)`
+fn parse_downloaded_bytes(line: &str) -> Option<u64> {
+    if !line.contains("Downloaded item") {
+        return None;
+    }
+    let start = line.rfind('(')? + 1;
+    let rest = line.get(start..)?;
+    rest.strip_suffix("bytes)")
+        .or_else(|| rest.strip_suffix("bytes)."))?
+        .trim()
+        .parse()
+        .ok()
+}
+
+// `Update state (0x61) downloading, progress: 12.34 (1234 / 5678)`
+fn parse_progress_total(line: &str) -> Option<u64> {
+    let rest = line.split("progress:").nth(1)?;
+    let start = rest.find('(')? + 1;
+    let end = rest.find(')')?;
+    rest.get(start..end)?.split('/').nth(1)?.trim().parse().ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn classifies_steamcmd_error_lines() {
+        let cases = [
+            (
+                "ERROR! Download item 2877640512 failed (Failure).",
+                FailureClass::Download,
+            ),
+            (
+                "ERROR! Download item 2877640512 failed (Access Denied).",
+                FailureClass::AccessDenied,
+            ),
+            (
+                "ERROR! Download item 2877640512 failed (Timeout).",
+                FailureClass::Timeout,
+            ),
+            (
+                "ERROR! Download item 2877640512 failed (Disk Write Failure).",
+                FailureClass::DiskFull,
+            ),
+            (
+                "ERROR! Failed to install app '4000' (No subscription)",
+                FailureClass::AccessDenied,
+            ),
+            (
+                "Error! App '740' state is 0x202 after update job.",
+                FailureClass::DiskFull,
+            ),
+            (
+                "Error! App '740' state is 0x602 after update job.",
+                FailureClass::Download,
+            ),
+            (
+                "Logging in user 'someone' to Steam Public...FAILED (Invalid Password)",
+                FailureClass::Login,
+            ),
+            (
+                "Logging in user 'someone' [U:1:0] to Steam Public...FAILED (Rate Limit Exceeded)",
+                FailureClass::Login,
+            ),
+            (
+                "FAILED login with result code Account Logon Denied",
+                FailureClass::Login,
+            ),
+            ("ERROR! Not logged on.", FailureClass::Login),
+        ];
+        for (line, class) in cases {
+            assert_eq!(classify_error(line), Some(class), "{}", line);
+        }
+    }
+
+    #[test]
+    fn ignores_lines_that_only_mention_failure() {
+        for line in [
+            "Loading Steam API...OK",
+            "Logging in user 'someone' to Steam Public...OK",
+            "Waiting for user info...OK",
+            "Update state (0x61) downloading, progress: 12.34 (1234 / 5678)",
+            "Success. Downloaded item 2877640512 to \"/tmp/failure\" (4567 bytes)",
+            "Redirecting stderr to '/home/user/Steam/logs/stderr.txt'",
+            "[ 45%] Downloading update (1,024 of 2,048 KB)...",
+            "password:",
+            "Steam Guard code:",
+            "Warning: failed (retrying) to fetch the app info",
+        ] {
+            assert_eq!(classify_error(line), None, "{}", line);
+        }
+    }
+}
@@ -0,0 +1,329 @@
+use std::fmt;
+use std::path::Path;
+use std::str::FromStr;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct AppId(u32);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct PublishedFileId(u64);
+
+fn parse_numeric_id<T: FromStr + PartialEq + Default>(s: &str, kind: &str) -> Result<T, String> {
+    let invalid = || format!("Invalid {}: {:?}", kind, s);
+    if s.is_empty() || !s.bytes().all(|b| b.is_ascii_digit()) {
+        return Err(invalid());
+    }
+    let id = s.parse::<T>().map_err(|_| invalid())?;
+    if id == T::default() {
+        return Err(invalid());
+    }
+    Ok(id)
+}
+
+impl FromStr for AppId {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        parse_numeric_id(s, "app id").map(AppId)
+    }
+}
+
+impl FromStr for PublishedFileId {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        parse_numeric_id(s, "workshop file id").map(PublishedFileId)
+    }
+}
+
+impl fmt::Display for AppId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+impl fmt::Display for PublishedFileId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+/// What a queue entry downloads: `<app_id>:<file_id>` for workshop items, `<app_id>` for apps.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SteamTarget {
+    Workshop {
+        app_id: AppId,
+        file_id: PublishedFileId,
+    },
+    App(AppId),
+}
+
+impl SteamTarget {
+    pub fn app_id(&self) -> AppId {
+        match self {
+            SteamTarget::Workshop { app_id, .. } | SteamTarget::App(app_id) => *app_id,
+        }
+    }
+
+    /// The id steamcmd names its content folder after.
+    pub fn content_id(&self) -> String {
+        match self {
+            SteamTarget::Workshop { file_id, .. } => file_id.to_string(),
+            SteamTarget::App(app_id) => app_id.to_string(),
+        }
+    }
+}
+
+impl FromStr for SteamTarget {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().split_once(':') {
+            Some((app, file)) => Ok(SteamTarget::Workshop {
+                app_id: app.parse()?,
+                file_id: file.parse()?,
+            }),
+            None => Ok(SteamTarget::App(s.trim().parse()?)),
+        }
+    }
+}
+
+impl fmt::Display for SteamTarget {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SteamTarget::Workshop { app_id, file_id } => write!(f, "{}:{}", app_id, file_id),
+            SteamTarget::App(app_id) => write!(f, "{}", app_id),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct SteamCmdScript {
+    commands: Vec<String>,
+    /// Index of an account `login`. steamcmd may ask for a password or a Steam Guard
+    /// code after it, so the commands that follow wait until it has logged in.
+    account_login: Option<usize>,
+    password: Option<Password>,
+}
+
+#[derive(Clone)]
+struct Password(String);
+
+impl fmt::Debug for Password {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("<hidden>")
+    }
+}
+
+impl SteamCmdScript {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn force_install_dir(mut self, dir: &Path) -> Result<Self, String> {
+        self.commands
+            .push(format!("force_install_dir {}", quote_path(dir)?));
+        Ok(self)
+    }
+
+    pub fn login_anonymous(mut self) -> Self {
+        self.commands.push("login anonymous".to_string());
+        self
+    }
+
+    /// `login <username>`, which uses steamcmd's cached login for the account when it
+    /// has one and otherwise prompts for `password`.
+    pub fn login_account(mut self, username: &str, password: Option<&str>) -> Result<Self, String> {
+        check_account_name(username)?;
+        if let Some(password) = password {
+            check_password(password)?;
+        }
+        self.account_login = Some(self.commands.len());
+        self.commands.push(format!("login {}", username));
+        self.password = password.map(|p| Password(p.to_string()));
+        Ok(self)
+    }
+
+    pub fn uses_account(&self) -> bool {
+        self.account_login.is_some()
+    }
+
+    pub fn password(&self) -> Option<&str> {
+        self.password.as_ref().map(|p| p.0.as_str())
+    }
+
+    pub fn workshop_download_item(mut self, app_id: AppId, file_id: PublishedFileId) -> Self {
+        self.commands
+            .push(format!("workshop_download_item {} {}", app_id, file_id));
+        self
+    }
+
+    pub fn app_update(mut self, app_id: AppId, validate: bool) -> Self {
+        let suffix = if validate { " validate" } else { "" };
+        self.commands
+            .push(format!("app_update {}{}", app_id, suffix));
+        self
+    }
+
+    pub fn download(self, target: SteamTarget) -> Self {
+        match target {
+            SteamTarget::Workshop { app_id, file_id } => {
+                self.workshop_download_item(app_id, file_id)
+            }
+            SteamTarget::App(app_id) => self.app_update(app_id, true),
+        }
+    }
+
+    /// Lines to feed to an interactive steamcmd over stdin, ending with `quit`.
+    pub fn render_stdin(&self) -> Vec<String> {
+        self.commands
+            .iter()
+            .cloned()
+            .chain(std::iter::once("quit".to_string()))
+            .collect()
+    }
+
+    /// `render_stdin` split after an account login: the lines to send right away, and
+    /// the ones to send once the login went through.
+    pub fn render_stdin_stages(&self) -> (Vec<String>, Vec<String>) {
+        let mut lines = self.render_stdin();
+        let rest = match self.account_login {
+            Some(index) => lines.split_off(index + 1),
+            None => Vec::new(),
+        };
+        (lines, rest)
+    }
+
+    /// File contents for `steamcmd +runscript <file>`.
+    pub fn render_runscript(&self) -> String {
+        let mut script = self.render_stdin().join("\n");
+        script.push('\n');
+        script
+    }
+}
+
+// steamcmd has no escape sequences inside quotes, so anything that could end the
+// quoted argument or the line is rejected outright.
+fn quote_path(path: &Path) -> Result<String, String> {
+    let s = path
+        .to_str()
+        .ok_or_else(|| format!("Path is not valid UTF-8: {}", path.display()))?;
+    if s.is_empty() || s.chars().any(|c| c == '"' || c.is_control()) {
+        return Err(format!("Unsupported characters in path: {:?}", s));
+    }
+    Ok(format!("\"{}\"", s))
+}
+
+/// Steam account names are letters, digits and underscores.
+pub fn check_account_name(name: &str) -> Result<(), String> {
+    if name.is_empty() || !name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_') {
+        return Err(format!("Invalid Steam account name: {:?}", name));
+    }
+    Ok(())
+}
+
+// The password is typed into steamcmd's prompt, where a line break would end it and
+// send the rest as a command.
+pub fn check_password(password: &str) -> Result<(), String> {
+    if password.is_empty() || password.chars().any(|c| c.is_control()) {
+        return Err("The password is empty or contains control characters".to_string());
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ids_must_be_plain_positive_numbers() {
+        assert_eq!(
+            "4000:2877640512"
+                .parse::<SteamTarget>()
+                .unwrap()
+                .to_string(),
+            "4000:2877640512"
+        );
+        assert_eq!(
+            " 740 ".parse::<SteamTarget>().unwrap(),
+            SteamTarget::App(AppId(740))
+        );
+        for id in [
+            "",
+            "0",
+            "4000:0",
+            "40 00",
+            "4000:1\nquit",
+            "4000;1",
+            "-4000",
+            "+4000",
+            "4000:1:2",
+        ] {
+            assert!(id.parse::<SteamTarget>().is_err(), "{:?} was accepted", id);
+        }
+    }
+
+    #[test]
+    fn quote_path_rejects_anything_that_ends_the_argument() {
+        assert_eq!(
+            quote_path(Path::new("/mods/Map Pack [1]")).unwrap(),
+            "\"/mods/Map Pack [1]\""
+        );
+        for path in ["", "/mods/a\"b", "/mods/a\nquit", "/mods/a\r", "/mods/a\0b"] {
+            assert!(
+                quote_path(Path::new(path)).is_err(),
+                "{:?} was accepted",
+                path
+            );
+        }
+    }
+
+    #[test]
+    fn renders_stdin_and_runscript() {
+        let target: SteamTarget = "4000:123".parse().unwrap();
+        let script = SteamCmdScript::new()
+            .force_install_dir(Path::new("/tmp/dl"))
+            .unwrap()
+            .login_anonymous()
+            .download(target);
+        assert_eq!(
+            script.render_stdin(),
+            [
+                "force_install_dir \"/tmp/dl\"",
+                "login anonymous",
+                "workshop_download_item 4000 123",
+                "quit",
+            ]
+        );
+        assert_eq!(
+            script.render_runscript(),
+            "force_install_dir \"/tmp/dl\"\nlogin anonymous\nworkshop_download_item 4000 123\nquit\n"
+        );
+        assert!(script.render_stdin_stages().1.is_empty());
+    }
+
+    #[test]
+    fn account_login_waits_before_the_rest_of_the_script() {
+        let script = SteamCmdScript::new()
+            .login_account("some_user", Some("hunter2"))
+            .unwrap()
+            .app_update("740".parse().unwrap(), true);
+        let (first, rest) = script.render_stdin_stages();
+        assert_eq!(first, ["login some_user"]);
+        assert_eq!(rest, ["app_update 740 validate", "quit"]);
+        assert_eq!(script.password(), Some("hunter2"));
+        assert!(!format!("{:?}", script).contains("hunter2"));
+    }
+
+    #[test]
+    fn rejects_account_names_and_passwords_that_inject_commands() {
+        for name in ["", "user name", "user\nquit", "user;quit"] {
+            assert!(SteamCmdScript::new().login_account(name, None).is_err());
+        }
+        for password in ["", "pass\nquit", "pass\r"] {
+            assert!(SteamCmdScript::new()
+                .login_account("user", Some(password))
+                .is_err());
+        }
+    }
+}
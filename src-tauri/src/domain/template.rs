@@ -0,0 +1,86 @@
+use crate::domain::sanitizer::sanitize_name;
+use std::path::PathBuf;
+
+pub struct TemplateVars<'a> {
+    pub app_id: &'a str,
+    pub app_name: &'a str,
+    pub title: &'a str,
+    pub file_id: &'a str,
+}
+
+/// Renders a folder template such as `{app_name}/{title} [{file_id}]` into a relative
+/// path. Each segment is sanitised on its own, so placeholders cannot introduce
+/// separators or `..`.
+pub fn render_folder_path(template: &str, vars: &TemplateVars) -> PathBuf {
+    let path: PathBuf = template
+        .split(['/', '\\'])
+        .map(|segment| render_segment(segment, vars))
+        .filter(|segment| !segment.trim().is_empty())
+        .map(|segment| sanitize_name(&segment))
+        .collect();
+
+    if path.as_os_str().is_empty() {
+        PathBuf::from(sanitize_name(vars.title))
+    } else {
+        path
+    }
+}
+
+fn render_segment(segment: &str, vars: &TemplateVars) -> String {
+    segment
+        .replace("{app_id}", vars.app_id)
+        .replace("{app_name}", vars.app_name)
+        .replace("{title}", vars.title)
+        .replace("{file_id}", vars.file_id)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::Path;
+
+    fn vars<'a>(title: &'a str, app_name: &'a str) -> TemplateVars<'a> {
+        TemplateVars {
+            app_id: "4000",
+            app_name,
+            title,
+            file_id: "123456",
+        }
+    }
+
+    #[test]
+    fn renders_placeholders_per_segment() {
+        assert_eq!(
+            render_folder_path(
+                "{app_name}/{title} [{file_id}]",
+                &vars("Map Pack", "Garry's Mod")
+            ),
+            Path::new("Garry's Mod").join("Map Pack [123456]")
+        );
+        assert_eq!(
+            render_folder_path("{app_id}\\{file_id}", &vars("Map Pack", "Garry's Mod")),
+            Path::new("4000").join("123456")
+        );
+    }
+
+    #[test]
+    fn placeholders_cannot_add_separators_or_climb_out() {
+        let path = render_folder_path("{app_name}/{title}", &vars("../../etc/passwd", ".."));
+        assert_eq!(path, Path::new("unnamed_item").join("_.._etc_passwd"));
+        assert!(path
+            .components()
+            .all(|c| matches!(c, std::path::Component::Normal(_))));
+    }
+
+    #[test]
+    fn empty_segments_are_dropped() {
+        assert_eq!(
+            render_folder_path("/{app_name}//{title}/", &vars("Map", "Game")),
+            Path::new("Game").join("Map")
+        );
+        assert_eq!(
+            render_folder_path("", &vars("Map", "Game")),
+            Path::new("Map")
+        );
+    }
+}
@@ -1,5 +1,6 @@
 use serde::{Deserialize, Serialize};
-use std::sync::{Arc, Mutex};
+use std::collections::HashMap;
+use std::sync::{mpsc, Arc, Mutex};
 
 #[derive(Clone, Default)]
 pub struct ProcessManagerState(
@@ -9,7 +10,18 @@ pub struct ProcessManagerState(
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub enum Status {
     Pending,
-    Downloading { progress: f32 },
+    Downloading {
+        progress: f32,
+    },
+    /// Held back until the target filesystems have `required` bytes free.
+    WaitingForSpace {
+        required: u64,
+        available: u64,
+    },
+    /// Copying from staging into the install folder.
+    Moving {
+        progress: f32,
+    },
     Completed,
     Failed(String),
 }
@@ -22,6 +34,40 @@ pub struct DownloadItem {
     pub status: Status,
     pub install_path: Option<String>,
     pub created_at: u64,
+    #[serde(default)]
+    pub app_name: Option<String>,
+    /// Root folder chosen at queue time, overriding the configured roots.
+    #[serde(default)]
+    pub destination: Option<String>,
+    #[serde(default)]
+    pub time_updated: Option<u64>,
+    #[serde(default)]
+    pub file_size: Option<u64>,
+    /// Per-item override of the app profile's `flatten`.
+    #[serde(default)]
+    pub flatten: Option<bool>,
+    #[serde(default)]
+    pub extract: Option<ExtractMode>,
+}
+
+/// Written before a download is moved into its install folder and removed once the
+/// item is recorded, so an interrupted move can be finished on the next start.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MoveJournal {
+    pub item: DownloadItem,
+    /// Content folder in the staging area.
+    pub src: String,
+    pub dst: String,
+    pub temp_dir: String,
+    /// Wrapper path being flattened away, as recorded in history.
+    pub flattened: Option<String>,
+    /// `dst` held this item before and is being replaced.
+    pub replace: bool,
+    pub reported_size: Option<u64>,
+    pub started_at: u64,
+    /// Where the replaced contents are kept as a previous version.
+    #[serde(default)]
+    pub snapshot: Option<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
@@ -47,14 +93,328 @@ impl QueueState {
     }
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub enum FailureClass {
+    Engine,
+    Login,
+    AccessDenied,
+    Timeout,
+    DiskFull,
+    Download,
+    FileSystem,
+    Collision,
+    Unknown,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct DownloadFailure {
+    pub class: FailureClass,
+    pub message: String,
+}
+
+impl DownloadFailure {
+    pub fn new(class: FailureClass, message: impl Into<String>) -> Self {
+        Self {
+            class,
+            message: message.into(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Default)]
+pub enum Outcome {
+    #[default]
+    Completed,
+    Failed(DownloadFailure),
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
 pub struct HistoryItem {
     pub id: String,
     pub steam_id: String,
+    /// Display title as published, before sanitising.
     pub name: String,
     pub install_path: String,
     pub timestamp: u64,
+    #[serde(default)]
+    pub outcome: Outcome,
+    #[serde(default)]
+    pub size_bytes: u64,
+    #[serde(default)]
+    pub file_count: u64,
+    #[serde(default)]
+    pub duration_secs: u64,
+    /// Bytes per second, based on the steamcmd-reported size when available.
+    #[serde(default)]
+    pub avg_speed: u64,
+    #[serde(default)]
+    pub reported_size: Option<u64>,
+    #[serde(default)]
+    pub deployments: Vec<Deployment>,
+    #[serde(default)]
+    pub time_updated: Option<u64>,
+    /// On-disk name of the install folder.
+    #[serde(default)]
+    pub folder_name: String,
+    /// Per-item override of `keep_versions`, carried over to later updates.
+    #[serde(default)]
+    pub keep_versions: Option<u32>,
+    /// Extraction chosen for this item, carried over to later updates.
+    #[serde(default)]
+    pub extract: Option<ExtractMode>,
+    /// Set when wrapper folders were flattened on install; holds the wrapper path that
+    /// was stripped (empty if there was nothing to unwrap).
+    #[serde(default)]
+    pub flattened: Option<String>,
+}
+
+/// What steamcmd itself is doing, apart from any download.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Default)]
+pub enum EngineState {
+    #[default]
+    Unprepared,
+    Extracting,
+    /// Bootstrapping or updating itself; the percentage when steamcmd prints one.
+    Updating {
+        progress: Option<f32>,
+    },
+    Ready,
+    Failed(String),
+}
+
+#[derive(Default)]
+pub struct EngineStatus(pub Mutex<EngineState>);
+
+/// How steamcmd logs in. Anonymous downloads fall back to the saved account when an
+/// item turns out to need ownership.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Default)]
+pub enum LoginMode {
+    #[default]
+    Anonymous,
+    Account,
 }
 
+/// Where a Steam Guard code goes while steamcmd waits for one.
+#[derive(Default)]
+pub struct SteamGuardState(pub Mutex<Option<mpsc::Sender<String>>>);
+
 #[derive(Clone, Default)]
 pub struct HistoryState(pub Arc<Mutex<Vec<HistoryItem>>>);
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Settings {
+    pub trash_retention_days: u64,
+    /// Library roots; the first one receives new downloads. Empty means `app_data/download`.
+    pub download_roots: Vec<String>,
+    pub folder_template: String,
+    /// Per-app overrides, keyed by app id.
+    pub app_profiles: HashMap<String, AppProfile>,
+    pub collision_policy: CollisionPolicy,
+    /// Previous versions kept when an item is updated.
+    pub keep_versions: u32,
+    /// Hardlink new installs against the content store.
+    pub dedupe: bool,
+    /// Use this steamcmd instead of the bundled one.
+    pub steamcmd_path: Option<String>,
+    pub login: LoginMode,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Self {
+            trash_retention_days: 7,
+            download_roots: Vec::new(),
+            folder_template: "{title}".to_string(),
+            app_profiles: HashMap::new(),
+            collision_policy: CollisionPolicy::default(),
+            keep_versions: 0,
+            dedupe: false,
+            steamcmd_path: None,
+            login: LoginMode::default(),
+        }
+    }
+}
+
+impl Settings {
+    pub fn profile(&self, app_id: &str) -> AppProfile {
+        self.app_profiles.get(app_id).cloned().unwrap_or_default()
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+#[serde(default)]
+pub struct AppProfile {
+    pub root: Option<String>,
+    pub folder_template: Option<String>,
+    pub deploy: Option<DeployTarget>,
+    pub collision_policy: Option<CollisionPolicy>,
+    /// Strip single-child wrapper folders when installing.
+    pub flatten: Option<bool>,
+    pub keep_versions: Option<u32>,
+    pub extract: Option<ExtractMode>,
+}
+
+/// Unpacking of archives shipped inside downloaded content: zip, tar and Garry's Mod
+/// `.gma` addons.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Default)]
+pub enum ExtractMode {
+    #[default]
+    Off,
+    KeepArchive,
+    DeleteArchive,
+}
+
+/// What to do when an install folder already holds a different item.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Default)]
+pub enum CollisionPolicy {
+    /// Use `<folder> [<file id>]` instead.
+    #[default]
+    Suffix,
+    Overwrite,
+    Fail,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Default)]
+pub enum DeployMode {
+    #[default]
+    Copy,
+    Symlink,
+    Hardlink,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DeployTarget {
+    /// The game's mod directory; each item is deployed into a subfolder named after its install folder.
+    pub dir: String,
+    #[serde(default)]
+    pub mode: DeployMode,
+    /// Deploy automatically when a download completes.
+    #[serde(default)]
+    pub auto: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct Deployment {
+    pub path: String,
+    pub mode: DeployMode,
+    pub deployed_at: u64,
+}
+
+#[derive(Clone, Default)]
+pub struct SettingsState(pub Arc<Mutex<Settings>>);
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TrashEntry {
+    pub id: String,
+    pub item: HistoryItem,
+    /// `None` when the history entry was removed but its files were kept.
+    pub trash_path: Option<String>,
+    pub trashed_at: u64,
+}
+
+#[derive(Clone, Default)]
+pub struct TrashState(pub Arc<Mutex<Vec<TrashEntry>>>);
+
+pub const MARKER_FILE_NAME: &str = ".steamdl.json";
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct InstallMarker {
+    pub steam_id: String,
+    pub name: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OrphanFolder {
+    pub path: String,
+    pub folder_name: String,
+    /// Full `app:file` id, only known when the folder has an install marker.
+    pub steam_id: Option<String>,
+    /// Workshop file id read from the folder name.
+    pub file_id: Option<String>,
+    pub name: Option<String>,
+    pub size_bytes: u64,
+}
+
+/// A steamcmd staging folder that no queued item is using, e.g. after a crash.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TempFolder {
+    pub path: String,
+    pub content_id: String,
+    /// Target to re-queue to pick up the partial data, when it can be told.
+    pub steam_id: Option<String>,
+    pub size_bytes: u64,
+}
+
+/// Problems found while starting up and the leftover downloads found then. `setup`
+/// runs before the window listens for events, so these are kept until it asks.
+#[derive(Debug, Clone, Serialize, Default)]
+pub struct StartupReport {
+    pub problems: Vec<String>,
+    pub temp_folders: Vec<TempFolder>,
+}
+
+#[derive(Default)]
+pub struct StartupState(pub Mutex<StartupReport>);
+
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct LibraryReport {
+    /// History ids whose install folder no longer exists.
+    pub missing: Vec<String>,
+    pub orphans: Vec<OrphanFolder>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct DedupeReport {
+    pub files_scanned: u64,
+    pub files_linked: u64,
+    /// Files that could not be linked, e.g. because they are on another filesystem.
+    pub files_skipped: u64,
+    pub bytes_saved: u64,
+    /// Store entries removed because no install used them any more.
+    pub bytes_freed: u64,
+}
+
+/// A previous version of an install, kept beside it in `.<folder>.versions/<id>/`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct VersionSnapshot {
+    pub id: String,
+    pub created_at: u64,
+    /// History entry the version was installed by.
+    pub item_id: String,
+    pub time_updated: Option<u64>,
+    pub size_bytes: u64,
+    pub file_count: u64,
+    pub flattened: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub enum ArchiveFormat {
+    Zip,
+    TarZst,
+}
+
+impl ArchiveFormat {
+    pub fn extension(self) -> &'static str {
+        match self {
+            ArchiveFormat::Zip => "zip",
+            ArchiveFormat::TarZst => "tar.zst",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExportEntry {
+    pub steam_id: String,
+    pub title: String,
+    pub app_id: Option<String>,
+    pub time_updated: Option<u64>,
+    /// Top-level folder of this item inside the archive.
+    pub folder: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExportManifest {
+    pub exported_at: u64,
+    pub items: Vec<ExportEntry>,
+}
@@ -1,5 +1,12 @@
+pub mod archiver;
 pub mod bin_loader;
+pub mod content_store;
+pub mod credential_store;
+pub mod deployer;
 pub mod file_manager;
+pub mod hasher;
+pub mod path_policy;
 pub mod persistence;
 pub mod process_manager;
 pub mod steam_client;
+pub mod workshop_api;
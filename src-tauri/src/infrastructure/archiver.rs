@@ -0,0 +1,621 @@
+use crate::domain::gma::{self, GmaHeader};
+use crate::domain::types::{ArchiveFormat, MARKER_FILE_NAME};
+use crate::infrastructure::file_manager;
+use std::fs::{self, File};
+use std::io::{self, BufReader, Cursor, Read, Write};
+use std::path::{Path, PathBuf};
+use zip::write::SimpleFileOptions;
+use zip::CompressionMethod;
+
+pub const EXPORT_MANIFEST_NAME: &str = "steamdl-export.json";
+const MAX_LEGACY_SIZE: u64 = 512 * 1024 * 1024;
+const MAX_ENTRIES: u64 = 1_000_000;
+/// Space left free on the disk an archive is unpacked to.
+const EXTRACT_HEADROOM: u64 = 64 * 1024 * 1024;
+
+pub struct ArchiveSource {
+    pub dir: PathBuf,
+    /// Folder name the contents are stored under inside the archive.
+    pub prefix: String,
+}
+
+struct ArchiveFile {
+    path: PathBuf,
+    name: String,
+    size: u64,
+}
+
+/// Total number of content bytes that [`write_archive`] will pack.
+pub fn total_size(sources: &[ArchiveSource]) -> io::Result<u64> {
+    Ok(list_files(sources)?.iter().map(|f| f.size).sum())
+}
+
+pub fn write_archive(
+    output: &Path,
+    format: ArchiveFormat,
+    manifest: &[u8],
+    sources: &[ArchiveSource],
+    mut on_progress: impl FnMut(u64),
+) -> io::Result<()> {
+    let files = list_files(sources)?;
+    let out = File::create(output)?;
+    let mut done = 0;
+    let mut progress = |n: u64| {
+        done += n;
+        on_progress(done);
+    };
+
+    match format {
+        ArchiveFormat::Zip => {
+            let mut zip = zip::ZipWriter::new(out);
+            let options = SimpleFileOptions::default()
+                .compression_method(CompressionMethod::Deflated)
+                .large_file(true);
+            zip.start_file(EXPORT_MANIFEST_NAME, options)?;
+            zip.write_all(manifest)?;
+            for file in &files {
+                zip.start_file(file.name.as_str(), options)?;
+                copy_with_progress(&mut File::open(&file.path)?, &mut zip, &mut progress)?;
+            }
+            zip.finish()?;
+        }
+        ArchiveFormat::TarZst => {
+            let encoder = zstd::Encoder::new(out, 3)?;
+            let mut tar = tar::Builder::new(encoder);
+            let mut header = tar::Header::new_gnu();
+            header.set_size(manifest.len() as u64);
+            header.set_mode(0o644);
+            header.set_cksum();
+            tar.append_data(&mut header, EXPORT_MANIFEST_NAME, manifest)?;
+            for file in &files {
+                let mut header = tar::Header::new_gnu();
+                header.set_metadata(&fs::metadata(&file.path)?);
+                let reader = ProgressReader {
+                    inner: File::open(&file.path)?,
+                    progress: &mut progress,
+                };
+                tar.append_data(&mut header, &file.name, reader)?;
+            }
+            tar.into_inner()?.finish()?;
+        }
+    }
+    Ok(())
+}
+
+/// Whether a downloaded file is an archive that [`extract_archive`] can unpack.
+pub fn is_extractable(path: &Path) -> bool {
+    extract_kind(path).is_some()
+}
+
+/// Archives below `dir`, found before anything is unpacked so that archives inside
+/// archives are left alone.
+pub fn find_archives(dir: &Path) -> io::Result<Vec<PathBuf>> {
+    let mut found = Vec::new();
+    for entry in fs::read_dir(dir)? {
+        let entry = entry?;
+        let path = entry.path();
+        if entry.file_type()?.is_dir() {
+            found.extend(find_archives(&path)?);
+        } else if is_extractable(&path) {
+            found.push(path);
+        }
+    }
+    Ok(found)
+}
+
+enum ExtractKind {
+    Zip,
+    Tar,
+    TarGz,
+    TarZst,
+    Gma,
+    /// An lzma-compressed `.gma`, as legacy Garry's Mod workshop items are delivered.
+    LegacyGma,
+}
+
+fn extract_kind(path: &Path) -> Option<ExtractKind> {
+    let name = path.file_name()?.to_string_lossy().to_lowercase();
+    if name.ends_with(".zip") {
+        Some(ExtractKind::Zip)
+    } else if name.ends_with(".tar") {
+        Some(ExtractKind::Tar)
+    } else if name.ends_with(".tar.gz") || name.ends_with(".tgz") {
+        Some(ExtractKind::TarGz)
+    } else if name.ends_with(".tar.zst") {
+        Some(ExtractKind::TarZst)
+    } else if name.ends_with(".gma") {
+        Some(ExtractKind::Gma)
+    } else if name.ends_with("_legacy.bin") {
+        Some(ExtractKind::LegacyGma)
+    } else {
+        None
+    }
+}
+
+/// Unpacks `archive` into `dest` and returns the number of files written. Entries that
+/// would land outside `dest` (absolute paths, `..`) fail the extraction; links are
+/// skipped, since they could point anywhere.
+///
+/// Everything is unpacked into a folder next to the archive first and only moved into
+/// `dest` once the whole archive has been read, so a broken archive leaves nothing
+/// behind and no entry can overwrite the archive itself. An archive that holds more
+/// than a million entries or would fill the disk is given up on the same way.
+pub fn extract_archive(archive: &Path, dest: &Path) -> io::Result<u64> {
+    let kind = extract_kind(archive).ok_or_else(|| {
+        io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!("Unsupported archive: {}", archive.display()),
+        )
+    })?;
+    let name = archive.file_name().unwrap_or_default().to_string_lossy();
+    let staging = archive.with_file_name(format!(".{}.steamdl-extract", name));
+    if staging.exists() {
+        fs::remove_dir_all(&staging)?;
+    }
+    fs::create_dir_all(&staging)?;
+    let result = Budget::for_dir(&staging)
+        .and_then(|mut budget| unpack(kind, archive, &staging, &mut budget))
+        .and_then(|count| {
+            if let Ok(own_path) = archive.strip_prefix(dest) {
+                if fs::symlink_metadata(staging.join(own_path)).is_ok() {
+                    return Err(invalid_data(format!(
+                        "Archive contains an entry that would replace itself: {}",
+                        own_path.display()
+                    )));
+                }
+            }
+            merge_into(&staging, dest)?;
+            Ok(count)
+        });
+    let _ = fs::remove_dir_all(&staging);
+    result
+}
+
+fn unpack(kind: ExtractKind, archive: &Path, dest: &Path, budget: &mut Budget) -> io::Result<u64> {
+    let file = File::open(archive)?;
+    match kind {
+        ExtractKind::Zip => extract_zip(file, dest, budget),
+        ExtractKind::Tar => extract_tar(file, dest, budget),
+        ExtractKind::TarGz => extract_tar(flate2::read::GzDecoder::new(file), dest, budget),
+        ExtractKind::TarZst => extract_tar(zstd::Decoder::new(file)?, dest, budget),
+        ExtractKind::Gma => extract_gma(BufReader::new(file), dest, budget),
+        ExtractKind::LegacyGma => extract_gma(decompress_legacy(file)?, dest, budget),
+    }
+}
+
+// What an archive may still unpack. Sizes stated in archive headers are not trusted,
+// so the bytes are counted as they are written.
+struct Budget {
+    bytes: u64,
+    entries: u64,
+}
+
+impl Budget {
+    fn for_dir(dir: &Path) -> io::Result<Budget> {
+        Ok(Budget {
+            bytes: file_manager::available_space(dir)?.saturating_sub(EXTRACT_HEADROOM),
+            entries: MAX_ENTRIES,
+        })
+    }
+
+    fn take_entry(&mut self) -> io::Result<()> {
+        self.entries = self.entries.checked_sub(1).ok_or_else(|| {
+            invalid_data(format!("Archive has more than {} entries", MAX_ENTRIES))
+        })?;
+        Ok(())
+    }
+
+    fn take_bytes(&mut self, size: u64) -> io::Result<()> {
+        self.bytes = self.bytes.checked_sub(size).ok_or_else(|| {
+            invalid_data("Archive unpacks to more than the free disk space".to_string())
+        })?;
+        Ok(())
+    }
+
+    fn copy(&mut self, reader: &mut impl Read, out: &mut impl Write) -> io::Result<()> {
+        let written = io::copy(&mut reader.take(self.bytes.saturating_add(1)), out)?;
+        self.take_bytes(written)
+    }
+}
+
+// Moves the unpacked tree into `dest`, replacing files that are already there.
+fn merge_into(from: &Path, dest: &Path) -> io::Result<()> {
+    fs::create_dir_all(dest)?;
+    for entry in fs::read_dir(from)? {
+        let entry = entry?;
+        let target = dest.join(entry.file_name());
+        let existing = fs::symlink_metadata(&target).ok();
+        if entry.file_type()?.is_dir() {
+            if existing.as_ref().is_some_and(|m| m.is_dir()) {
+                merge_into(&entry.path(), &target)?;
+                continue;
+            }
+            if existing.is_some() {
+                fs::remove_file(&target)?;
+            }
+        } else if existing.as_ref().is_some_and(|m| m.is_dir()) {
+            fs::remove_dir_all(&target)?;
+        }
+        fs::rename(entry.path(), &target)?;
+    }
+    Ok(())
+}
+
+/// Reads the header and file table of a `.gma` or `_legacy.bin` addon.
+pub fn read_gma(path: &Path) -> io::Result<GmaHeader> {
+    let file = File::open(path)?;
+    let header = match extract_kind(path) {
+        Some(ExtractKind::LegacyGma) => gma::read_header(&mut decompress_legacy(file)?),
+        _ => gma::read_header(&mut BufReader::new(file)),
+    };
+    header.map_err(invalid_data)
+}
+
+// Legacy addons predate the workshop size limits of today and are small enough to
+// unpack in memory. The size in the LZMA header is checked up front and enforced while
+// decoding, so a corrupt or hostile file cannot claim more.
+fn decompress_legacy(file: File) -> io::Result<Cursor<Vec<u8>>> {
+    let mut reader = BufReader::new(file);
+    // Properties (1 byte), dictionary size (4), unpacked size (8, all ones if unknown).
+    let mut header = [0u8; 13];
+    reader.read_exact(&mut header)?;
+    let declared = u64::from_le_bytes(header[5..].try_into().unwrap_or_default());
+    if declared != u64::MAX && declared > MAX_LEGACY_SIZE {
+        return Err(invalid_data(format!(
+            "Legacy addon claims to unpack to {} bytes",
+            declared
+        )));
+    }
+    let mut out = LimitedWriter {
+        data: Vec::new(),
+        limit: declared.min(MAX_LEGACY_SIZE),
+    };
+    let options = lzma_rs::decompress::Options {
+        memlimit: Some(MAX_LEGACY_SIZE as usize),
+        ..Default::default()
+    };
+    lzma_rs::lzma_decompress_with_options(
+        &mut Cursor::new(header).chain(reader),
+        &mut out,
+        &options,
+    )
+    .map_err(|e| invalid_data(e.to_string()))?;
+    Ok(Cursor::new(out.data))
+}
+
+struct LimitedWriter {
+    data: Vec<u8>,
+    limit: u64,
+}
+
+impl Write for LimitedWriter {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        if (self.data.len() + buf.len()) as u64 > self.limit {
+            return Err(invalid_data(format!(
+                "Legacy addon unpacks to more than {} bytes",
+                self.limit
+            )));
+        }
+        self.data.extend_from_slice(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+fn extract_gma(mut reader: impl Read, dest: &Path, budget: &mut Budget) -> io::Result<u64> {
+    let header = gma::read_header(&mut reader).map_err(invalid_data)?;
+    for entry in &header.files {
+        budget.take_entry()?;
+        budget.take_bytes(entry.size)?;
+        let target = dest.join(contained_path(&entry.path)?);
+        if let Some(parent) = target.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        let mut out = File::create(&target)?;
+        if let Err(e) = gma::copy_entry(&mut reader, entry, &mut out) {
+            drop(out);
+            let _ = fs::remove_file(&target);
+            return Err(invalid_data(e));
+        }
+    }
+    Ok(header.files.len() as u64)
+}
+
+fn invalid_data(message: String) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message)
+}
+
+fn extract_zip(file: File, dest: &Path, budget: &mut Budget) -> io::Result<u64> {
+    let mut zip = zip::ZipArchive::new(file)?;
+    let mut written = 0;
+    for index in 0..zip.len() {
+        budget.take_entry()?;
+        let mut entry = zip.by_index(index)?;
+        let target = dest.join(contained_path(entry.name())?);
+        if entry.is_dir() {
+            fs::create_dir_all(&target)?;
+        } else if !entry.is_symlink() {
+            let mode = entry.unix_mode();
+            write_entry(&mut entry, &target, mode, budget)?;
+            written += 1;
+        }
+    }
+    Ok(written)
+}
+
+fn extract_tar(reader: impl Read, dest: &Path, budget: &mut Budget) -> io::Result<u64> {
+    let mut tar = tar::Archive::new(reader);
+    let mut written = 0;
+    for entry in tar.entries()? {
+        budget.take_entry()?;
+        let mut entry = entry?;
+        let target = dest.join(contained_path(&entry.path()?.to_string_lossy())?);
+        let entry_type = entry.header().entry_type();
+        if entry_type.is_dir() {
+            fs::create_dir_all(&target)?;
+        } else if entry_type.is_file() {
+            let mode = entry.header().mode().ok();
+            write_entry(&mut entry, &target, mode, budget)?;
+            written += 1;
+        }
+    }
+    Ok(written)
+}
+
+fn write_entry(
+    reader: &mut impl Read,
+    target: &Path,
+    mode: Option<u32>,
+    budget: &mut Budget,
+) -> io::Result<()> {
+    if let Some(parent) = target.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    let mut out = File::create(target)?;
+    budget.copy(reader, &mut out)?;
+    #[cfg(unix)]
+    if let Some(mode) = mode.filter(|m| m & 0o111 != 0) {
+        use std::os::unix::fs::PermissionsExt;
+        fs::set_permissions(target, fs::Permissions::from_mode(mode & 0o755))?;
+    }
+    #[cfg(not(unix))]
+    let _ = mode;
+    Ok(())
+}
+
+// Keeps only plain name components, rejecting anything that climbs out of the
+// extraction folder or is rooted elsewhere. Both separators count, so a name written
+// on Windows cannot slip `..\` or a drive prefix past the check on other platforms.
+fn contained_path(name: &str) -> io::Result<PathBuf> {
+    let escapes = || invalid_data(format!("Archive entry escapes the target folder: {}", name));
+    if name.starts_with(['/', '\\']) {
+        return Err(escapes());
+    }
+    let mut path = PathBuf::new();
+    for part in name.split(['/', '\\']) {
+        match part {
+            "" | "." => {}
+            ".." => return Err(escapes()),
+            _ if part.contains(':') => return Err(escapes()),
+            _ => path.push(part),
+        }
+    }
+    Ok(path)
+}
+
+fn list_files(sources: &[ArchiveSource]) -> io::Result<Vec<ArchiveFile>> {
+    let mut files = Vec::new();
+    for source in sources {
+        collect(&source.dir, &source.prefix, true, &mut files)?;
+    }
+    Ok(files)
+}
+
+fn collect(dir: &Path, prefix: &str, top: bool, out: &mut Vec<ArchiveFile>) -> io::Result<()> {
+    for entry in fs::read_dir(dir)? {
+        let entry = entry?;
+        let file_name = entry.file_name().to_string_lossy().to_string();
+        if top && file_name == MARKER_FILE_NAME {
+            continue;
+        }
+        let name = format!("{}/{}", prefix, file_name);
+        if entry.file_type()?.is_dir() {
+            collect(&entry.path(), &name, false, out)?;
+        } else {
+            out.push(ArchiveFile {
+                size: entry.metadata()?.len(),
+                path: entry.path(),
+                name,
+            });
+        }
+    }
+    Ok(())
+}
+
+fn copy_with_progress(
+    reader: &mut impl Read,
+    writer: &mut impl Write,
+    progress: &mut impl FnMut(u64),
+) -> io::Result<()> {
+    let mut buf = vec![0u8; 1 << 16];
+    loop {
+        let n = reader.read(&mut buf)?;
+        if n == 0 {
+            return Ok(());
+        }
+        writer.write_all(&buf[..n])?;
+        progress(n as u64);
+    }
+}
+
+struct ProgressReader<'a, R, F> {
+    inner: R,
+    progress: &'a mut F,
+}
+
+impl<R: Read, F: FnMut(u64)> Read for ProgressReader<'_, R, F> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let n = self.inner.read(buf)?;
+        (self.progress)(n as u64);
+        Ok(n)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn scratch_dir() -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("steamdl-test-{}", uuid::Uuid::new_v4()));
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    fn file_header(size: usize) -> tar::Header {
+        let mut header = tar::Header::new_gnu();
+        header.set_size(size as u64);
+        header.set_mode(0o644);
+        header.set_cksum();
+        header
+    }
+
+    fn unlimited() -> Budget {
+        Budget {
+            bytes: u64::MAX,
+            entries: MAX_ENTRIES,
+        }
+    }
+
+    #[test]
+    fn extraction_stops_at_the_budget() {
+        let dir = scratch_dir();
+        let mut tar = tar::Builder::new(Vec::new());
+        tar.append_data(&mut file_header(2), "a.txt", &b"hi"[..])
+            .unwrap();
+        tar.append_data(&mut file_header(3), "b.txt", &b"big"[..])
+            .unwrap();
+        let data = tar.into_inner().unwrap();
+
+        let mut budget = Budget {
+            bytes: 4,
+            entries: MAX_ENTRIES,
+        };
+        assert!(extract_tar(Cursor::new(data.clone()), &dir, &mut budget).is_err());
+        let mut budget = Budget {
+            bytes: u64::MAX,
+            entries: 1,
+        };
+        assert!(extract_tar(Cursor::new(data), &dir, &mut budget).is_err());
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn legacy_addons_are_bounded_by_their_header() {
+        let dir = scratch_dir();
+        let mut packed = Vec::new();
+        lzma_rs::lzma_compress(&mut &b"GMAD payload"[..], &mut packed).unwrap();
+        let path = dir.join("addon_legacy.bin");
+        fs::write(&path, &packed).unwrap();
+        let data = decompress_legacy(File::open(&path).unwrap()).unwrap();
+        assert_eq!(data.into_inner(), b"GMAD payload");
+
+        packed[5..13].copy_from_slice(&(MAX_LEGACY_SIZE + 1).to_le_bytes());
+        fs::write(&path, &packed).unwrap();
+        assert!(decompress_legacy(File::open(&path).unwrap()).is_err());
+
+        packed[5..13].copy_from_slice(&4u64.to_le_bytes());
+        fs::write(&path, &packed).unwrap();
+        let data = decompress_legacy(File::open(&path).unwrap()).unwrap();
+        assert_eq!(data.into_inner(), b"GMAD");
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn contained_path_keeps_plain_names() {
+        assert_eq!(
+            contained_path("maps/./de_dust.bsp").unwrap(),
+            Path::new("maps").join("de_dust.bsp")
+        );
+        assert_eq!(
+            contained_path("maps\\de_dust.bsp").unwrap(),
+            Path::new("maps").join("de_dust.bsp")
+        );
+    }
+
+    #[test]
+    fn contained_path_rejects_escapes() {
+        for name in [
+            "../evil.txt",
+            "maps/../../evil.txt",
+            "/etc/passwd",
+            "..\\evil.txt",
+            "\\\\server\\share\\evil.txt",
+            "C:\\Windows\\evil.dll",
+            "C:evil.dll",
+            "file.txt:stream",
+        ] {
+            assert!(contained_path(name).is_err(), "{} was accepted", name);
+        }
+    }
+
+    #[test]
+    fn tar_symlink_entries_are_skipped() {
+        let dir = scratch_dir();
+        let mut tar = tar::Builder::new(Vec::new());
+        let mut link = tar::Header::new_gnu();
+        link.set_entry_type(tar::EntryType::Symlink);
+        link.set_size(0);
+        tar.append_link(&mut link, "passwd", "/etc/passwd").unwrap();
+        tar.append_data(&mut file_header(2), "a.txt", &b"hi"[..])
+            .unwrap();
+        let data = tar.into_inner().unwrap();
+
+        assert_eq!(
+            extract_tar(Cursor::new(data), &dir, &mut unlimited()).unwrap(),
+            1
+        );
+        assert!(fs::symlink_metadata(dir.join("passwd")).is_err());
+        assert_eq!(fs::read(dir.join("a.txt")).unwrap(), b"hi");
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn entry_named_like_the_archive_leaves_it_intact() {
+        let dir = scratch_dir();
+        let mut tar = tar::Builder::new(Vec::new());
+        tar.append_data(&mut file_header(2), "a.txt", &b"hi"[..])
+            .unwrap();
+        tar.append_data(&mut file_header(1), "pack.tar", &b"x"[..])
+            .unwrap();
+        let data = tar.into_inner().unwrap();
+        let archive = dir.join("pack.tar");
+        fs::write(&archive, &data).unwrap();
+
+        assert!(extract_archive(&archive, &dir).is_err());
+        assert_eq!(fs::read(&archive).unwrap(), data);
+        assert!(!dir.join("a.txt").exists());
+        assert_eq!(fs::read_dir(&dir).unwrap().count(), 1);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn extraction_merges_into_existing_folders() {
+        let dir = scratch_dir();
+        fs::create_dir_all(dir.join("maps")).unwrap();
+        fs::write(dir.join("maps/old.bsp"), b"old").unwrap();
+        let mut tar = tar::Builder::new(Vec::new());
+        tar.append_data(&mut file_header(3), "maps/new.bsp", &b"new"[..])
+            .unwrap();
+        let archive = dir.join("pack.tar");
+        fs::write(&archive, tar.into_inner().unwrap()).unwrap();
+
+        assert_eq!(extract_archive(&archive, &dir).unwrap(), 1);
+        assert_eq!(fs::read(dir.join("maps/old.bsp")).unwrap(), b"old");
+        assert_eq!(fs::read(dir.join("maps/new.bsp")).unwrap(), b"new");
+        assert!(!dir.join(".pack.tar.steamdl-extract").exists());
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -1,23 +1,135 @@
+use crate::domain::steamcmd_output::EngineStart;
+#[cfg(target_os = "linux")]
+use crate::domain::types::ProcessManagerState;
+use crate::domain::types::{EngineState, EngineStatus, SettingsState};
+use crate::infrastructure::process_manager::ProcessManager;
 use std::fs;
-use std::path::PathBuf;
-use tauri::{AppHandle, Manager};
+use std::io::{BufRead, BufReader, Write};
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::{mpsc, Mutex, MutexGuard};
+use std::thread;
+use std::time::Duration;
+use tauri::{AppHandle, Emitter, Manager};
 
 #[cfg(target_os = "windows")]
 const STEAMCMD_BYTES: &[u8] = include_bytes!("../../bin/steamcmd-x86_64-pc-windows-msvc.exe");
 #[cfg(target_os = "macos")]
 const STEAMCMD_BYTES: &[u8] = include_bytes!("../../bin/steamcmd-aarch64-apple-darwin");
 
+#[cfg(target_os = "linux")]
+const STEAMCMD_LINUX_URL: &str =
+    "https://steamcdn-a.akamaihd.net/client/installer/steamcmd_linux.tar.gz";
+/// sha256 of the bootstrap archive. A download that does not match is never unpacked.
+/// Valve replaces the archive in place, so this pin has to follow it; until it does,
+/// the engine set up before or a steamcmd found on the system is used.
+#[cfg(target_os = "linux")]
+const STEAMCMD_LINUX_SHA256: &str = "";
+/// Written when setting up the bootstrap failed, with the pin it was tried against, so
+/// the download is not repeated for every job.
+#[cfg(target_os = "linux")]
+const BOOTSTRAP_FAILED: &str = ".steamdl-bootstrap-failed";
+#[cfg(target_os = "linux")]
+const BOOTSTRAP_RETRY: Duration = Duration::from_secs(24 * 60 * 60);
+
+/// Names steamcmd goes by on `PATH` and in its install folder.
+#[cfg(target_os = "windows")]
+const STEAMCMD_NAMES: &[&str] = &["steamcmd.exe"];
+#[cfg(not(target_os = "windows"))]
+const STEAMCMD_NAMES: &[&str] = &["steamcmd", "steamcmd.sh"];
+
+/// Records which engine was extracted, so a different one shipped with an app update
+/// replaces it. Only written once extraction has finished.
+const ENGINE_STAMP: &str = ".steamdl-engine";
+/// Broken starts in a row after which the engine folder is rebuilt.
+const MAX_ENGINE_FAILURES: u32 = 2;
+static ENGINE_FAILURES: AtomicU32 = AtomicU32::new(0);
+/// Held while steamcmd runs from the engine folder, so a warm-up and a download never
+/// update it at the same time.
+static ENGINE_RUN: Mutex<()> = Mutex::new(());
+
+/// A first run updates steamcmd before it prints anything useful, which can take a
+/// while on a slow connection.
+const CHECK_TIMEOUT: Duration = Duration::from_secs(120);
+/// Lines only steamcmd prints while starting up.
+const STEAMCMD_SIGNATURES: &[&str] = &[
+    "Steam Console Client",
+    "Checking for available updates",
+    "Verifying installation",
+];
+
+/// The steamcmd from the settings if one is configured, otherwise the bundled one.
 pub fn get_steamcmd_path(app: &AppHandle) -> Result<PathBuf, String> {
+    let configured = app
+        .try_state::<SettingsState>()
+        .and_then(|state| state.0.lock().ok().and_then(|s| s.steamcmd_path.clone()));
+    if let Some(path) = configured {
+        let path = PathBuf::from(path);
+        if !path.is_file() {
+            return Err(format!("Configured steamcmd not found: {}", path.display()));
+        }
+        return Ok(path);
+    }
+
     let exe_name = if cfg!(target_os = "windows") {
         "steamcmd.exe"
+    } else if cfg!(target_os = "linux") {
+        "steamcmd.sh"
     } else {
         "steamcmd"
     };
-    let app_data = app.path().app_data_dir().map_err(|e| e.to_string())?;
-    let bin_path = app_data.join("engine").join(exe_name);
+    let engine_dir = engine_dir(app)?;
+    let bin_path = engine_dir.join(exe_name);
+    let stamp_path = engine_dir.join(ENGINE_STAMP);
+    let stamped = fs::read_to_string(&stamp_path).is_ok_and(|s| s == engine_stamp());
 
-    if !bin_path.exists() {
-        extract_bin(&bin_path)?;
+    if !bin_path.exists() || !stamped {
+        // A recent failed attempt with the same pin is reported again instead of
+        // downloading the bootstrap for every job.
+        #[cfg(target_os = "linux")]
+        let earlier_failure = bootstrap_failure(&engine_dir);
+        #[cfg(not(target_os = "linux"))]
+        let earlier_failure: Option<String> = None;
+        #[cfg(target_os = "linux")]
+        let retried = earlier_failure.is_none();
+
+        let result = match earlier_failure {
+            Some(e) => Err(e),
+            None => {
+                set_engine_state(app, EngineState::Extracting);
+                let result = extract_bin(&bin_path);
+                #[cfg(target_os = "linux")]
+                record_bootstrap(&engine_dir, &result);
+                result
+            }
+        };
+        match result {
+            Ok(()) => fs::write(&stamp_path, engine_stamp()).map_err(|e| e.to_string())?,
+            #[cfg(target_os = "linux")]
+            Err(e) if !bin_path.exists() => {
+                return fallback_steamcmd(app).ok_or_else(|| {
+                    let message = format!("Could not set up steamcmd: {}", e);
+                    set_engine_state(app, EngineState::Failed(message.clone()));
+                    message
+                });
+            }
+            // The bootstrap could not be fetched again; the one set up before still works.
+            #[cfg(target_os = "linux")]
+            Err(e) => {
+                if retried {
+                    let _ = app.emit(
+                        "terminal-output",
+                        format!("Could not refresh steamcmd, using the existing one: {}", e),
+                    );
+                }
+            }
+            #[cfg(not(target_os = "linux"))]
+            Err(e) => {
+                set_engine_state(app, EngineState::Failed(e.clone()));
+                return Err(e);
+            }
+        }
     }
 
     if !bin_path.exists() {
@@ -30,18 +142,330 @@ pub fn get_steamcmd_path(app: &AppHandle) -> Result<PathBuf, String> {
     Ok(bin_path)
 }
 
-fn extract_bin(path: &PathBuf) -> Result<(), String> {
+/// The error of the last attempt to set up the bootstrap, if it was made against the
+/// current pin within [`BOOTSTRAP_RETRY`].
+#[cfg(target_os = "linux")]
+fn bootstrap_failure(engine_dir: &Path) -> Option<String> {
+    let marker = engine_dir.with_file_name(BOOTSTRAP_FAILED);
+    let age = fs::metadata(&marker)
+        .ok()?
+        .modified()
+        .ok()?
+        .elapsed()
+        .ok()?;
+    let contents = fs::read_to_string(&marker).ok()?;
+    let (pin, error) = contents.split_once('\n')?;
+    (age < BOOTSTRAP_RETRY && pin == engine_stamp()).then(|| error.to_string())
+}
+
+#[cfg(target_os = "linux")]
+fn record_bootstrap(engine_dir: &Path, result: &Result<(), String>) {
+    let marker = engine_dir.with_file_name(BOOTSTRAP_FAILED);
+    match result {
+        Ok(()) => {
+            let _ = fs::remove_file(&marker);
+        }
+        Err(e) => {
+            let _ = fs::write(&marker, format!("{}\n{}", engine_stamp(), e));
+        }
+    }
+}
+
+/// A steamcmd found on the system that passed [`check_steamcmd`], used while the
+/// bootstrap cannot be fetched. Checked once and reused while it is still there.
+#[cfg(target_os = "linux")]
+fn fallback_steamcmd(app: &AppHandle) -> Option<PathBuf> {
+    static CHECKED: Mutex<Option<PathBuf>> = Mutex::new(None);
+    let mut checked = CHECKED.lock().unwrap_or_else(|e| e.into_inner());
+    if let Some(path) = checked.as_ref().filter(|p| p.is_file()) {
+        return Some(path.clone());
+    }
+    let processes = app
+        .try_state::<ProcessManagerState>()?
+        .0
+        .lock()
+        .ok()?
+        .processes
+        .clone();
+    let process_manager = ProcessManager { processes };
+    *checked = detect_steamcmd().into_iter().find(|path| {
+        let result = check_steamcmd(path, &process_manager);
+        if let Err(e) = &result {
+            let _ = app.emit(
+                "terminal-output",
+                format!("Skipping {}: {}", path.display(), e),
+            );
+        }
+        result.is_ok()
+    });
+    checked.clone()
+}
+
+fn engine_dir(app: &AppHandle) -> Result<PathBuf, String> {
+    Ok(app
+        .path()
+        .app_data_dir()
+        .map_err(|e| e.to_string())?
+        .join("engine"))
+}
+
+#[cfg(not(target_os = "linux"))]
+fn engine_stamp() -> &'static str {
+    use sha2::{Digest, Sha256};
+    use std::sync::OnceLock;
+    static STAMP: OnceLock<String> = OnceLock::new();
+    STAMP.get_or_init(|| format!("{:x}", Sha256::digest(STEAMCMD_BYTES)))
+}
+
+// The bootstrap is downloaded rather than embedded; it only changes with the pin.
+#[cfg(target_os = "linux")]
+fn engine_stamp() -> &'static str {
+    STEAMCMD_LINUX_SHA256
+}
+
+pub fn lock_engine() -> MutexGuard<'static, ()> {
+    ENGINE_RUN.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+pub fn current_engine_state(app: &AppHandle) -> EngineState {
+    app.try_state::<EngineStatus>()
+        .and_then(|status| status.0.lock().ok().map(|s| s.clone()))
+        .unwrap_or_default()
+}
+
+/// Records the engine state and emits `engine-state` when it changed.
+pub fn set_engine_state(app: &AppHandle, state: EngineState) {
+    if let Some(status) = app.try_state::<EngineStatus>() {
+        if let Ok(mut current) = status.0.lock() {
+            if *current == state {
+                return;
+            }
+            *current = state.clone();
+        }
+    }
+    let _ = app.emit("engine-state", state);
+}
+
+/// Called after each steamcmd run with how far it got. Only runs that point at a
+/// damaged engine count towards rebuilding it; failing to update while offline does not.
+pub fn report_engine_start(app: &AppHandle, start: EngineStart) {
+    let message = match start {
+        EngineStart::Started => {
+            ENGINE_FAILURES.store(0, Ordering::Relaxed);
+            set_engine_state(app, EngineState::Ready);
+            return;
+        }
+        EngineStart::UpdateFailed => "steamcmd could not update itself; it needs a connection",
+        EngineStart::Stopped | EngineStart::Broken => "steamcmd exited while starting up",
+    };
+    set_engine_state(app, EngineState::Failed(message.to_string()));
+    if start != EngineStart::Broken {
+        return;
+    }
+    let failures = ENGINE_FAILURES.fetch_add(1, Ordering::Relaxed) + 1;
+    if failures < MAX_ENGINE_FAILURES {
+        return;
+    }
+    ENGINE_FAILURES.store(0, Ordering::Relaxed);
+    let custom = app
+        .try_state::<SettingsState>()
+        .and_then(|state| state.0.lock().ok().map(|s| s.steamcmd_path.is_some()))
+        .unwrap_or(false);
+    if custom {
+        let _ = app.emit(
+            "terminal-output",
+            "The configured steamcmd keeps failing to start; check it or switch back to the bundled one",
+        );
+        return;
+    }
+    let message = match engine_dir(app).and_then(|dir| reset_engine(&dir)) {
+        Ok(()) => "steamcmd failed to start repeatedly; its folder was reset".to_string(),
+        Err(e) => format!(
+            "steamcmd failed to start repeatedly and could not be reset: {}",
+            e
+        ),
+    };
+    let _ = app.emit("terminal-output", message);
+}
+
+// The embedded engine is extracted again on the next run.
+#[cfg(not(target_os = "linux"))]
+fn reset_engine(dir: &Path) -> Result<(), String> {
+    match fs::remove_dir_all(dir) {
+        Err(e) if e.kind() != std::io::ErrorKind::NotFound => Err(e.to_string()),
+        _ => Ok(()),
+    }
+}
+
+// The bootstrap has to be downloaded, so the old engine is only replaced once a new
+// one is ready beside it.
+#[cfg(target_os = "linux")]
+fn reset_engine(dir: &Path) -> Result<(), String> {
+    let staged = dir.with_file_name("engine.new");
+    let _ = fs::remove_dir_all(&staged);
+    let result = extract_bin(&staged.join("steamcmd.sh")).and_then(|()| {
+        fs::write(staged.join(ENGINE_STAMP), engine_stamp()).map_err(|e| e.to_string())
+    });
+    record_bootstrap(dir, &result);
+    if let Err(e) = result {
+        let _ = fs::remove_dir_all(&staged);
+        return Err(e);
+    }
+    match fs::remove_dir_all(dir) {
+        Err(e) if e.kind() != std::io::ErrorKind::NotFound => return Err(e.to_string()),
+        _ => {}
+    }
+    fs::rename(&staged, dir).map_err(|e| e.to_string())
+}
+
+/// steamcmd installs found on `PATH` and in the places it is usually put.
+pub fn detect_steamcmd() -> Vec<PathBuf> {
+    let mut dirs: Vec<PathBuf> = std::env::var_os("PATH")
+        .map(|path| std::env::split_paths(&path).collect())
+        .unwrap_or_default();
+    dirs.extend(common_locations());
+
+    let mut found: Vec<PathBuf> = Vec::new();
+    for dir in dirs {
+        for name in STEAMCMD_NAMES {
+            let candidate = dir.join(name);
+            if candidate.is_file() && !found.contains(&candidate) {
+                found.push(candidate);
+            }
+        }
+    }
+    found
+}
+
+fn common_locations() -> Vec<PathBuf> {
+    let mut dirs = Vec::new();
+    #[cfg(target_os = "windows")]
+    {
+        dirs.push(PathBuf::from(r"C:\steamcmd"));
+        for var in ["ProgramFiles", "ProgramFiles(x86)", "USERPROFILE"] {
+            if let Some(base) = std::env::var_os(var) {
+                dirs.push(PathBuf::from(base).join("steamcmd"));
+            }
+        }
+    }
+    #[cfg(not(target_os = "windows"))]
+    {
+        dirs.extend(
+            ["/usr/games", "/usr/bin", "/usr/local/bin", "/opt/steamcmd"].map(PathBuf::from),
+        );
+        #[cfg(target_os = "macos")]
+        dirs.push(PathBuf::from("/opt/homebrew/bin"));
+        if let Some(home) = std::env::var_os("HOME") {
+            let home = PathBuf::from(home);
+            dirs.push(home.join("steamcmd"));
+            dirs.push(home.join("Steam").join("steamcmd"));
+            dirs.push(home.join(".steam").join("steamcmd"));
+        }
+    }
+    dirs
+}
+
+/// Runs `path` with `+quit` and checks that it starts up as steamcmd.
+pub fn check_steamcmd(path: &Path, process_manager: &ProcessManager) -> Result<(), String> {
+    if !path.is_file() {
+        return Err(format!("{} is not a file", path.display()));
+    }
+    let mut cmd = Command::new(path);
+    cmd.arg("+quit");
+    let mut child = process_manager
+        .spawn_process(&mut cmd)
+        .map_err(|e| format!("Failed to run {}: {}", path.display(), e))?;
+    let pid = child.id();
+    if let Some(mut stdin) = child.stdin.take() {
+        let _ = writeln!(stdin, "quit");
+    }
+
+    let (tx, rx) = mpsc::channel();
+    if let Some(stdout) = child.stdout.take() {
+        thread::spawn(move || {
+            let found = BufReader::new(stdout)
+                .lines()
+                .map_while(Result::ok)
+                .any(|line| STEAMCMD_SIGNATURES.iter().any(|s| line.contains(s)));
+            let _ = tx.send(found);
+        });
+    }
+    let result = rx.recv_timeout(CHECK_TIMEOUT);
+    process_manager.kill(pid);
+    let _ = child.wait();
+    match result {
+        Ok(true) => Ok(()),
+        Ok(false) => Err(format!("{} does not look like steamcmd", path.display())),
+        Err(_) => Err(format!(
+            "{} did not respond within {} seconds",
+            path.display(),
+            CHECK_TIMEOUT.as_secs()
+        )),
+    }
+}
+
+#[cfg(not(target_os = "linux"))]
+fn extract_bin(path: &Path) -> Result<(), String> {
     if let Some(parent) = path.parent() {
         fs::create_dir_all(parent).map_err(|e| e.to_string())?;
     }
     fs::write(path, STEAMCMD_BYTES).map_err(|e| e.to_string())?;
 
     #[cfg(unix)]
-    {
-        use std::os::unix::fs::PermissionsExt;
-        let mut perms = fs::metadata(path).map_err(|e| e.to_string())?.permissions();
-        perms.set_mode(0o755);
-        fs::set_permissions(path, perms).map_err(|e| e.to_string())?;
-    }
+    make_executable(path)?;
     Ok(())
 }
+
+/// Valve only ships a bootstrap archive for Linux: `steamcmd.sh` plus the `linux32`
+/// runtime it launches. It is fetched and unpacked into the engine folder.
+#[cfg(target_os = "linux")]
+fn extract_bin(path: &Path) -> Result<(), String> {
+    if engine_stamp().is_empty() {
+        return Err(
+            "This build has no pinned checksum for steamcmd_linux.tar.gz; install steamcmd \
+             or set its path in Settings"
+                .to_string(),
+        );
+    }
+    let engine_dir = path.parent().ok_or("Invalid engine path")?;
+    fs::create_dir_all(engine_dir).map_err(|e| e.to_string())?;
+
+    let output = std::process::Command::new("curl")
+        .args(["-s", "-f", "-L", "--proto", "=https", STEAMCMD_LINUX_URL])
+        .output()
+        .map_err(|e| format!("Failed to run curl: {}", e))?;
+    if !output.status.success() || output.stdout.is_empty() {
+        return Err("Failed to download steamcmd_linux.tar.gz".to_string());
+    }
+    let digest = {
+        use sha2::{Digest, Sha256};
+        format!("{:x}", Sha256::digest(&output.stdout))
+    };
+    // Valve replaces the archive in place, so a mismatch usually means the pin is out
+    // of date rather than a damaged download; it is never unpacked either way.
+    if !digest.eq_ignore_ascii_case(engine_stamp()) {
+        return Err(format!(
+            "The steamcmd pin is out of date: steamcmd_linux.tar.gz now has sha256 {} but \
+             this build expects {}. Update STEAMCMD_LINUX_SHA256 after checking the new \
+             archive, or set a steamcmd path in Settings",
+            digest,
+            engine_stamp()
+        ));
+    }
+
+    let decoder = flate2::read::GzDecoder::new(output.stdout.as_slice());
+    tar::Archive::new(decoder)
+        .unpack(engine_dir)
+        .map_err(|e| format!("Failed to unpack steamcmd: {}", e))?;
+
+    make_executable(path)?;
+    make_executable(&engine_dir.join("linux32").join("steamcmd"))
+}
+
+#[cfg(unix)]
+fn make_executable(path: &Path) -> Result<(), String> {
+    use std::os::unix::fs::PermissionsExt;
+    let mut perms = fs::metadata(path).map_err(|e| e.to_string())?.permissions();
+    perms.set_mode(0o755);
+    fs::set_permissions(path, perms).map_err(|e| e.to_string())
+}
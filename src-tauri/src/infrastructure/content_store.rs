@@ -0,0 +1,203 @@
+use crate::domain::types::DedupeReport;
+use crate::infrastructure::hasher::hash_file;
+use std::collections::HashSet;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// Files smaller than this are left alone; linking them saves next to nothing.
+const MIN_DEDUPE_SIZE: u64 = 16 * 1024;
+
+/// Files stored once under their sha256 and hardlinked into install folders. A blob
+/// whose only remaining link is the store's own is no longer used by any install.
+///
+/// Every install holding a blob shares one inode, so nothing else may link to them:
+/// hardlink deployments copy shared files and kept versions are unshared when they
+/// are set aside.
+pub struct ContentStore {
+    root: PathBuf,
+    // Blobs re-hashed during this run. A blob still changes if something writes to an
+    // install in place, so its name is not trusted without checking.
+    verified: HashSet<String>,
+}
+
+impl ContentStore {
+    pub fn new(root: PathBuf) -> Self {
+        Self {
+            root,
+            verified: HashSet::new(),
+        }
+    }
+
+    /// Replaces every file below `dir` that has a copy in the store with a hardlink to
+    /// it, and adds the rest to the store.
+    pub fn dedupe_dir(&mut self, dir: &Path, report: &mut DedupeReport) -> io::Result<()> {
+        for entry in fs::read_dir(dir)? {
+            let entry = entry?;
+            let path = entry.path();
+            let file_type = entry.file_type()?;
+            if file_type.is_dir() {
+                self.dedupe_dir(&path, report)?;
+            } else if file_type.is_file() {
+                self.dedupe_file(&path, report)?;
+            }
+        }
+        Ok(())
+    }
+
+    fn dedupe_file(&mut self, path: &Path, report: &mut DedupeReport) -> io::Result<()> {
+        let size = fs::metadata(path)?.len();
+        if size < MIN_DEDUPE_SIZE {
+            return Ok(());
+        }
+        report.files_scanned += 1;
+        let hash = hash_file(path)?;
+        let blob = self.blob_path(&hash);
+
+        if !self.blob_matches(&blob, &hash)? {
+            fs::create_dir_all(blob.parent().unwrap_or(&self.root))?;
+            if fs::hard_link(path, &blob).is_err() {
+                // Another filesystem or no hardlink support; the file stays as it is.
+                report.files_skipped += 1;
+            }
+            self.verified.insert(hash);
+            return Ok(());
+        }
+        if same_file(path, &blob)? {
+            return Ok(());
+        }
+
+        let temp = path.with_file_name(format!(
+            "{}.steamdl-link",
+            path.file_name().unwrap_or_default().to_string_lossy()
+        ));
+        let _ = fs::remove_file(&temp);
+        if fs::hard_link(&blob, &temp).is_err() {
+            report.files_skipped += 1;
+            return Ok(());
+        }
+        if let Err(e) = fs::rename(&temp, path) {
+            let _ = fs::remove_file(&temp);
+            return Err(e);
+        }
+        report.files_linked += 1;
+        report.bytes_saved += size;
+        Ok(())
+    }
+
+    // Whether the store holds `hash` with the right contents. A blob that no longer
+    // matches is dropped from the store; files linked to it keep their contents.
+    fn blob_matches(&mut self, blob: &Path, hash: &str) -> io::Result<bool> {
+        if !blob.exists() {
+            return Ok(false);
+        }
+        if self.verified.contains(hash) {
+            return Ok(true);
+        }
+        if hash_file(blob)? == hash {
+            self.verified.insert(hash.to_string());
+            return Ok(true);
+        }
+        fs::remove_file(blob)?;
+        Ok(false)
+    }
+
+    /// Removes blobs no install links to any more and returns the bytes freed.
+    pub fn collect_garbage(&self) -> io::Result<u64> {
+        let mut freed = 0;
+        let shards = match fs::read_dir(&self.root) {
+            Ok(shards) => shards,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(0),
+            Err(e) => return Err(e),
+        };
+        for shard in shards {
+            let shard = shard?.path();
+            if !shard.is_dir() {
+                continue;
+            }
+            for blob in fs::read_dir(&shard)? {
+                let blob = blob?.path();
+                if link_count(&blob)? <= 1 {
+                    freed += fs::metadata(&blob)?.len();
+                    fs::remove_file(&blob)?;
+                }
+            }
+            let _ = fs::remove_dir(&shard);
+        }
+        Ok(freed)
+    }
+
+    fn blob_path(&self, hash: &str) -> PathBuf {
+        self.root.join(&hash[..2]).join(hash)
+    }
+}
+
+#[cfg(unix)]
+fn file_identity(path: &Path) -> io::Result<(u64, u64, u64)> {
+    use std::os::unix::fs::MetadataExt;
+    let meta = fs::metadata(path)?;
+    Ok((meta.dev(), meta.ino(), meta.nlink()))
+}
+
+#[cfg(windows)]
+fn file_identity(path: &Path) -> io::Result<(u64, u64, u64)> {
+    use std::os::windows::io::AsRawHandle;
+    use windows_sys::Win32::Storage::FileSystem::{
+        GetFileInformationByHandle, BY_HANDLE_FILE_INFORMATION,
+    };
+    let file = fs::File::open(path)?;
+    // SAFETY: the handle is valid for the lifetime of `file` and `info` is a plain
+    // struct the call fills in.
+    let info = unsafe {
+        let mut info: BY_HANDLE_FILE_INFORMATION = std::mem::zeroed();
+        if GetFileInformationByHandle(file.as_raw_handle(), &mut info) == 0 {
+            return Err(io::Error::last_os_error());
+        }
+        info
+    };
+    let index = ((info.nFileIndexHigh as u64) << 32) | info.nFileIndexLow as u64;
+    Ok((
+        info.dwVolumeSerialNumber as u64,
+        index,
+        info.nNumberOfLinks as u64,
+    ))
+}
+
+/// Whether the file has other hardlinks, e.g. into the content store.
+pub fn is_shared(path: &Path) -> io::Result<bool> {
+    Ok(link_count(path)? > 1)
+}
+
+/// Gives every file below `dir` that shares its contents through a hardlink a copy of
+/// its own, so writes to it no longer reach the other links.
+pub fn unshare_dir(dir: &Path) -> io::Result<()> {
+    for entry in fs::read_dir(dir)? {
+        let entry = entry?;
+        let path = entry.path();
+        let file_type = entry.file_type()?;
+        if file_type.is_dir() {
+            unshare_dir(&path)?;
+        } else if file_type.is_file() && is_shared(&path)? {
+            let temp = path.with_file_name(format!(
+                "{}.steamdl-copy",
+                path.file_name().unwrap_or_default().to_string_lossy()
+            ));
+            fs::copy(&path, &temp)?;
+            if let Err(e) = fs::rename(&temp, &path) {
+                let _ = fs::remove_file(&temp);
+                return Err(e);
+            }
+        }
+    }
+    Ok(())
+}
+
+fn same_file(a: &Path, b: &Path) -> io::Result<bool> {
+    let (dev_a, ino_a, _) = file_identity(a)?;
+    let (dev_b, ino_b, _) = file_identity(b)?;
+    Ok(dev_a == dev_b && ino_a == ino_b)
+}
+
+fn link_count(path: &Path) -> io::Result<u64> {
+    file_identity(path).map(|(_, _, links)| links)
+}
@@ -0,0 +1,61 @@
+use keyring::Entry;
+use serde::{Deserialize, Serialize};
+
+const ACCOUNT_ENTRY: &str = "steam-account";
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct SteamCredentials {
+    pub username: String,
+    /// Not needed once steamcmd has a cached login for the account.
+    pub password: Option<String>,
+}
+
+/// The Steam account, kept in the operating system's credential store (Windows
+/// Credential Manager, the macOS keychain or the Secret Service on Linux) so nothing
+/// secret is written to the data folder. Without one, such as on a Linux desktop that
+/// runs neither GNOME Keyring nor KWallet, no account can be saved and downloads stay
+/// anonymous.
+pub struct CredentialStore {
+    entry: Entry,
+}
+
+impl CredentialStore {
+    /// `service` names the application in the credential store.
+    pub fn new(service: &str) -> Result<Self, String> {
+        let entry = Entry::new(service, ACCOUNT_ENTRY).map_err(store_error)?;
+        Ok(Self { entry })
+    }
+
+    pub fn load(&self) -> Result<Option<SteamCredentials>, String> {
+        match self.entry.get_password() {
+            Ok(json) => serde_json::from_str(&json)
+                .map(Some)
+                .map_err(|_| "Saved credentials are corrupt".to_string()),
+            Err(keyring::Error::NoEntry) => Ok(None),
+            Err(e) => Err(store_error(e)),
+        }
+    }
+
+    pub fn save(&self, credentials: &SteamCredentials) -> Result<(), String> {
+        let json = serde_json::to_string(credentials).map_err(|e| e.to_string())?;
+        self.entry.set_password(&json).map_err(store_error)
+    }
+
+    pub fn clear(&self) -> Result<(), String> {
+        match self.entry.delete_credential() {
+            Ok(()) | Err(keyring::Error::NoEntry) => Ok(()),
+            Err(e) => Err(store_error(e)),
+        }
+    }
+}
+
+fn store_error(e: keyring::Error) -> String {
+    match e {
+        keyring::Error::PlatformFailure(_) | keyring::Error::NoStorageAccess(_) => format!(
+            "No system credential store is available to keep a Steam account in; on Linux \
+             this needs a Secret Service provider such as GNOME Keyring or KWallet ({})",
+            e
+        ),
+        e => e.to_string(),
+    }
+}
@@ -0,0 +1,102 @@
+use crate::domain::types::{DeployMode, MARKER_FILE_NAME};
+use crate::infrastructure::content_store::is_shared;
+use std::cell::Cell;
+use std::fs;
+use std::io;
+use std::path::Path;
+
+/// Returns how many files a hardlink deployment had to copy instead.
+pub fn deploy(src: &Path, dst: &Path, mode: DeployMode) -> io::Result<u64> {
+    if let Some(parent) = dst.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    match mode {
+        DeployMode::Copy => mirror_tree(src, dst, |from, to| fs::copy(from, to).map(|_| ()))?,
+        DeployMode::Hardlink => {
+            let copied = Cell::new(0);
+            mirror_tree(src, dst, |from, to| {
+                let shared = link_unshared(from, to)?;
+                copied.set(copied.get() + u64::from(shared));
+                Ok(())
+            })?;
+            return Ok(copied.get());
+        }
+        DeployMode::Symlink => symlink_dir(src, dst)?,
+    }
+    Ok(0)
+}
+
+pub fn undeploy(dst: &Path, mode: DeployMode) -> io::Result<()> {
+    let meta = match fs::symlink_metadata(dst) {
+        Ok(meta) => meta,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(()),
+        Err(e) => return Err(e),
+    };
+    if mode == DeployMode::Symlink || meta.file_type().is_symlink() {
+        remove_symlink(dst)
+    } else {
+        fs::remove_dir_all(dst)
+    }
+}
+
+/// Hardlinks the files of `src` into `dst`, copying those that cannot be linked, e.g.
+/// across filesystems.
+pub fn link_tree(src: &Path, dst: &Path) -> io::Result<()> {
+    mirror_tree(src, dst, |from, to| {
+        fs::hard_link(from, to).or_else(|_| fs::copy(from, to).map(|_| ()))
+    })
+}
+
+fn mirror_tree(
+    src: &Path,
+    dst: &Path,
+    link: impl Fn(&Path, &Path) -> io::Result<()> + Copy,
+) -> io::Result<()> {
+    fs::create_dir_all(dst)?;
+    for entry in fs::read_dir(src)? {
+        let entry = entry?;
+        if entry.file_name() == MARKER_FILE_NAME {
+            continue;
+        }
+        let target = dst.join(entry.file_name());
+        if entry.file_type()?.is_dir() {
+            mirror_tree(&entry.path(), &target, link)?;
+        } else {
+            link(&entry.path(), &target)?;
+        }
+    }
+    Ok(())
+}
+
+// A file that already has other links, such as a deduplicated one, is copied: a game
+// writing to the deployment would otherwise change every install sharing it. Returns
+// whether it was copied.
+fn link_unshared(from: &Path, to: &Path) -> io::Result<bool> {
+    if is_shared(from)? {
+        fs::copy(from, to)?;
+        Ok(true)
+    } else {
+        fs::hard_link(from, to)?;
+        Ok(false)
+    }
+}
+
+#[cfg(unix)]
+fn symlink_dir(src: &Path, dst: &Path) -> io::Result<()> {
+    std::os::unix::fs::symlink(src, dst)
+}
+
+#[cfg(windows)]
+fn symlink_dir(src: &Path, dst: &Path) -> io::Result<()> {
+    std::os::windows::fs::symlink_dir(src, dst)
+}
+
+#[cfg(unix)]
+fn remove_symlink(path: &Path) -> io::Result<()> {
+    fs::remove_file(path)
+}
+
+#[cfg(windows)]
+fn remove_symlink(path: &Path) -> io::Result<()> {
+    fs::remove_dir(path)
+}
@@ -1,38 +1,260 @@
-use std::fs;
-use std::path::Path;
+use sha2::{Digest, Sha256};
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, Read, Write};
+use std::path::{Path, PathBuf};
 
-pub fn move_recursive(src: &Path, dst: &Path) -> std::io::Result<()> {
+use crate::infrastructure::hasher::hash_file;
+
+const PART_SUFFIX: &str = ".steamdl-part";
+
+pub fn move_recursive(src: &Path, dst: &Path) -> io::Result<()> {
+    move_with_progress(src, dst, &mut |_, _| {})
+}
+
+/// Moves `src` to `dst`. When a rename is not possible the tree is copied, each file
+/// is checked against its source by size and hash, and the source is removed only
+/// once everything has been copied. Running it again after an interruption keeps the
+/// files already completed and continues a partly copied one.
+pub fn move_with_progress(
+    src: &Path,
+    dst: &Path,
+    on_progress: &mut dyn FnMut(u64, u64),
+) -> io::Result<()> {
     if fs::rename(src, dst).is_ok() {
         return Ok(());
     }
+    let (total, _) = dir_stats(src)?;
+    let mut copied = 0;
+    copy_verified(src, dst, &mut |n| {
+        copied += n;
+        on_progress(copied, total);
+    })?;
+    if src.is_dir() {
+        fs::remove_dir_all(src)
+    } else {
+        fs::remove_file(src)
+    }
+}
+
+fn copy_verified(src: &Path, dst: &Path, progress: &mut dyn FnMut(u64)) -> io::Result<()> {
     if src.is_dir() {
         fs::create_dir_all(dst)?;
         for entry in fs::read_dir(src)? {
             let entry = entry?;
-            move_recursive(&entry.path(), &dst.join(entry.file_name()))?;
+            copy_verified(&entry.path(), &dst.join(entry.file_name()), progress)?;
         }
-        fs::remove_dir_all(src)?;
+        return Ok(());
+    }
+
+    let len = fs::metadata(src)?.len();
+    // A file already at `dst` is either one an earlier run completed or something else
+    // with the same name; only skip it when its contents match the source.
+    if fs::metadata(dst).map(|m| m.len() == len).unwrap_or(false)
+        && hash_file(dst)? == hash_file(src)?
+    {
+        progress(len);
+        return Ok(());
+    }
+
+    let mut part_name = dst.file_name().unwrap_or_default().to_os_string();
+    part_name.push(PART_SUFFIX);
+    let part = dst.with_file_name(part_name);
+    let expected = copy_file_resumable(src, &part, len, progress)?;
+    if fs::metadata(&part)?.len() != len || hash_file(&part)? != expected {
+        let _ = fs::remove_file(&part);
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("Copy of {} does not match the source", src.display()),
+        ));
+    }
+    fs::rename(&part, dst)
+}
+
+// Appends the rest of `src` to `part` and returns the hash of the whole source.
+fn copy_file_resumable(
+    src: &Path,
+    part: &Path,
+    len: u64,
+    progress: &mut dyn FnMut(u64),
+) -> io::Result<String> {
+    let mut input = File::open(src)?;
+    let mut output = OpenOptions::new().create(true).append(true).open(part)?;
+    let mut done = output.metadata()?.len();
+    if done > len {
+        output.set_len(0)?;
+        done = 0;
+    }
+
+    let mut hasher = Sha256::new();
+    let mut buf = vec![0u8; 1 << 16];
+    let mut skipped = 0;
+    while skipped < done {
+        let want = buf.len().min((done - skipped) as usize);
+        let n = input.read(&mut buf[..want])?;
+        if n == 0 {
+            break;
+        }
+        hasher.update(&buf[..n]);
+        skipped += n as u64;
+    }
+    progress(done);
+
+    loop {
+        let n = input.read(&mut buf)?;
+        if n == 0 {
+            break;
+        }
+        output.write_all(&buf[..n])?;
+        hasher.update(&buf[..n]);
+        progress(n as u64);
+    }
+    output.sync_all()?;
+    Ok(format!("{:x}", hasher.finalize()))
+}
+
+/// Moves new contents in over an existing `dst`. They are written to a sibling
+/// folder first and swapped in with two renames, so `dst` is never half-written. The
+/// old contents are then moved to `retain`, or removed. The staged copy is kept when
+/// anything fails, so calling it again continues the copy and `finish_replace`
+/// completes the swap.
+pub fn replace_dir<T>(
+    dst: &Path,
+    retain: Option<&Path>,
+    move_in: impl FnOnce(&Path) -> io::Result<T>,
+) -> io::Result<T> {
+    let staged = sibling_path(dst, "steamdl-new");
+    let value = move_in(&staged)?;
+    swap_in(dst, &staged, retain)?;
+    Ok(value)
+}
+
+pub fn finish_replace(dst: &Path, retain: Option<&Path>) -> io::Result<()> {
+    let staged = sibling_path(dst, "steamdl-new");
+    if staged.exists() {
+        swap_in(dst, &staged, retain)
     } else {
-        fs::copy(src, dst)?;
-        fs::remove_file(src)?;
+        set_aside(&sibling_path(dst, "steamdl-old"), retain)
+    }
+}
+
+fn swap_in(dst: &Path, staged: &Path, retain: Option<&Path>) -> io::Result<()> {
+    let backup = sibling_path(dst, "steamdl-old");
+    if dst.exists() {
+        discard(&backup)?;
+        fs::rename(dst, &backup)?;
+    }
+    if let Err(e) = fs::rename(staged, dst) {
+        let _ = fs::rename(&backup, dst);
+        return Err(e);
+    }
+    set_aside(&backup, retain)
+}
+
+fn set_aside(backup: &Path, retain: Option<&Path>) -> io::Result<()> {
+    match retain {
+        Some(retain) if backup.exists() => {
+            if let Some(parent) = retain.parent() {
+                fs::create_dir_all(parent)?;
+            }
+            fs::rename(backup, retain)
+        }
+        _ => discard(backup),
+    }
+}
+
+fn discard(dir: &Path) -> io::Result<()> {
+    if dir.exists() {
+        fs::remove_dir_all(dir)?;
     }
     Ok(())
 }
 
-pub fn hoist(src: &Path, dst: &Path) -> std::io::Result<()> {
+/// The staged and set-aside folders `replace_dir` keeps next to `dst` during a swap.
+pub fn replace_siblings(dst: &Path) -> [PathBuf; 2] {
+    [
+        sibling_path(dst, "steamdl-new"),
+        sibling_path(dst, "steamdl-old"),
+    ]
+}
+
+fn sibling_path(dst: &Path, suffix: &str) -> PathBuf {
+    let name = dst.file_name().unwrap_or_default().to_string_lossy();
+    dst.with_file_name(format!(".{}.{}", name, suffix))
+}
+
+/// The folder below `src` reached by descending through single-child directories.
+pub fn wrapper_root(src: &Path) -> io::Result<PathBuf> {
     let mut current = src.to_path_buf();
     loop {
         let entries: Vec<_> = fs::read_dir(&current)?.collect::<Result<Vec<_>, _>>()?;
         if entries.len() == 1 && entries[0].path().is_dir() {
             current = entries[0].path();
         } else {
-            break;
+            return Ok(current);
         }
     }
-    move_recursive(&current, dst)
 }
 
-pub fn open_path(path: &str) -> std::io::Result<()> {
+/// Moves `src` to `dst`, descending through single-child directories first.
+pub fn hoist(src: &Path, dst: &Path, on_progress: &mut dyn FnMut(u64, u64)) -> io::Result<()> {
+    move_with_progress(&wrapper_root(src)?, dst, on_progress)
+}
+
+pub fn dir_stats(path: &Path) -> io::Result<(u64, u64)> {
+    let meta = fs::symlink_metadata(path)?;
+    if !meta.is_dir() {
+        return Ok((meta.len(), 1));
+    }
+    let (mut bytes, mut files) = (0, 0);
+    for entry in fs::read_dir(path)? {
+        let (b, f) = dir_stats(&entry?.path())?;
+        bytes += b;
+        files += f;
+    }
+    Ok((bytes, files))
+}
+
+/// Free bytes on the filesystem holding `path`, which need not exist yet.
+pub fn available_space(path: &Path) -> io::Result<u64> {
+    fs2::available_space(existing_ancestor(path)?)
+}
+
+pub fn same_filesystem(a: &Path, b: &Path) -> io::Result<bool> {
+    let (a, b) = (existing_ancestor(a)?, existing_ancestor(b)?);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::MetadataExt;
+        Ok(fs::metadata(a)?.dev() == fs::metadata(b)?.dev())
+    }
+    #[cfg(not(unix))]
+    {
+        let volume = |p: &Path| -> io::Result<_> {
+            Ok(p.canonicalize()?
+                .components()
+                .next()
+                .map(|c| c.as_os_str().to_os_string()))
+        };
+        Ok(volume(a)? == volume(b)?)
+    }
+}
+
+pub fn is_disk_full(e: &io::Error) -> bool {
+    matches!(
+        e.kind(),
+        io::ErrorKind::StorageFull | io::ErrorKind::QuotaExceeded
+    )
+}
+
+fn existing_ancestor(path: &Path) -> io::Result<&Path> {
+    path.ancestors().find(|p| p.exists()).ok_or_else(|| {
+        io::Error::new(
+            io::ErrorKind::NotFound,
+            format!("No existing folder above {}", path.display()),
+        )
+    })
+}
+
+pub fn open_path(path: &str) -> io::Result<()> {
     #[cfg(target_os = "windows")]
     {
         std::process::Command::new("explorer").arg(path).spawn()?;
@@ -41,6 +263,10 @@ pub fn open_path(path: &str) -> std::io::Result<()> {
     {
         std::process::Command::new("open").arg(path).spawn()?;
     }
+    #[cfg(all(unix, not(target_os = "macos")))]
+    {
+        std::process::Command::new("xdg-open").arg(path).spawn()?;
+    }
     Ok(())
 }
 
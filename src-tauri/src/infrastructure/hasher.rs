@@ -0,0 +1,54 @@
+use crate::domain::manifest::ManifestEntry;
+use crate::domain::types::MARKER_FILE_NAME;
+use sha2::{Digest, Sha256};
+use std::fs::{self, File};
+use std::io::{self, Read};
+use std::path::Path;
+
+pub fn hash_file(path: &Path) -> io::Result<String> {
+    let mut file = File::open(path)?;
+    let mut hasher = Sha256::new();
+    let mut buf = vec![0u8; 1 << 16];
+    loop {
+        let n = file.read(&mut buf)?;
+        if n == 0 {
+            break;
+        }
+        hasher.update(&buf[..n]);
+    }
+    Ok(format!("{:x}", hasher.finalize()))
+}
+
+/// Hashes every file below `root`, skipping our own marker file.
+pub fn build_manifest(root: &Path) -> io::Result<Vec<ManifestEntry>> {
+    let mut entries = Vec::new();
+    collect(root, "", &mut entries)?;
+    entries.sort_by(|a, b| a.path.cmp(&b.path));
+    Ok(entries)
+}
+
+fn collect(dir: &Path, prefix: &str, out: &mut Vec<ManifestEntry>) -> io::Result<()> {
+    for entry in fs::read_dir(dir)? {
+        let entry = entry?;
+        let name = entry.file_name().to_string_lossy().to_string();
+        if prefix.is_empty() && name == MARKER_FILE_NAME {
+            continue;
+        }
+        let relative = if prefix.is_empty() {
+            name
+        } else {
+            format!("{}/{}", prefix, name)
+        };
+        let file_type = entry.file_type()?;
+        if file_type.is_dir() {
+            collect(&entry.path(), &relative, out)?;
+        } else {
+            out.push(ManifestEntry {
+                size: entry.metadata()?.len(),
+                sha256: hash_file(&entry.path())?,
+                path: relative,
+            });
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,145 @@
+use std::path::{Component, Path, PathBuf};
+
+/// Restricts filesystem operations to a fixed set of managed roots.
+pub struct PathPolicy {
+    roots: Vec<PathBuf>,
+}
+
+impl PathPolicy {
+    pub fn new(roots: impl IntoIterator<Item = PathBuf>) -> Self {
+        Self {
+            roots: roots
+                .into_iter()
+                .map(|root| root.canonicalize().unwrap_or(root))
+                .collect(),
+        }
+    }
+
+    /// Resolves an existing path, following symlinks, and checks that it lies strictly
+    /// inside one of the roots.
+    pub fn resolve(&self, path: &Path) -> Result<PathBuf, String> {
+        let canonical = path
+            .canonicalize()
+            .map_err(|e| format!("Cannot resolve {}: {}", path.display(), e))?;
+        if self.contains(&canonical) {
+            Ok(canonical)
+        } else {
+            Err(outside_error(path))
+        }
+    }
+
+    /// Like [`resolve`](Self::resolve), but does not follow the final component, so a
+    /// symlink inside a root can be checked without resolving where it points.
+    pub fn resolve_entry(&self, path: &Path) -> Result<PathBuf, String> {
+        let name = path.file_name().ok_or_else(|| outside_error(path))?;
+        let parent = path.parent().ok_or_else(|| outside_error(path))?;
+        let resolved = parent
+            .canonicalize()
+            .map_err(|e| format!("Cannot resolve {}: {}", parent.display(), e))?
+            .join(name);
+        if self.contains(&resolved) {
+            Ok(resolved)
+        } else {
+            Err(outside_error(path))
+        }
+    }
+
+    /// Checks a path that may not exist yet. The deepest existing ancestor is
+    /// canonicalised so a symlinked parent cannot escape a root.
+    pub fn check_target(&self, path: &Path) -> Result<PathBuf, String> {
+        if !path.is_absolute() || path.components().any(|c| c == Component::ParentDir) {
+            return Err(outside_error(path));
+        }
+        let mut existing = path;
+        let mut rest = Vec::new();
+        while !existing.exists() {
+            rest.push(existing.file_name().ok_or_else(|| outside_error(path))?);
+            existing = existing.parent().ok_or_else(|| outside_error(path))?;
+        }
+        let mut resolved = existing.canonicalize().map_err(|e| e.to_string())?;
+        resolved.extend(rest.into_iter().rev());
+        if self.contains(&resolved) {
+            Ok(resolved)
+        } else {
+            Err(outside_error(path))
+        }
+    }
+
+    fn contains(&self, path: &Path) -> bool {
+        self.roots
+            .iter()
+            .any(|root| path != root && path.starts_with(root))
+    }
+}
+
+fn outside_error(path: &Path) -> String {
+    format!("Path is outside the managed folders: {}", path.display())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    struct Scratch(PathBuf);
+
+    impl Scratch {
+        fn new() -> Self {
+            let dir = std::env::temp_dir().join(format!("steamdl-test-{}", uuid::Uuid::new_v4()));
+            fs::create_dir_all(dir.join("root/item")).unwrap();
+            fs::create_dir_all(dir.join("outside")).unwrap();
+            Scratch(dir.canonicalize().unwrap())
+        }
+
+        fn policy(&self) -> PathPolicy {
+            PathPolicy::new([self.0.join("root")])
+        }
+    }
+
+    impl Drop for Scratch {
+        fn drop(&mut self) {
+            let _ = fs::remove_dir_all(&self.0);
+        }
+    }
+
+    #[test]
+    fn resolve_allows_only_paths_inside_a_root() {
+        let scratch = Scratch::new();
+        let policy = scratch.policy();
+        let item = scratch.0.join("root/item");
+        assert_eq!(policy.resolve(&item).unwrap(), item);
+        assert!(policy.resolve(&scratch.0.join("root")).is_err());
+        assert!(policy.resolve(&scratch.0.join("outside")).is_err());
+        assert!(policy
+            .resolve(&scratch.0.join("root/item/../../outside"))
+            .is_err());
+        assert!(policy.resolve(&scratch.0.join("root/missing")).is_err());
+    }
+
+    #[test]
+    fn check_target_rejects_relative_and_parent_paths() {
+        let scratch = Scratch::new();
+        let policy = scratch.policy();
+        let new_dir = scratch.0.join("root/new/nested");
+        assert_eq!(policy.check_target(&new_dir).unwrap(), new_dir);
+        assert!(policy.check_target(Path::new("root/new")).is_err());
+        assert!(policy
+            .check_target(&scratch.0.join("root/new/../../outside"))
+            .is_err());
+        assert!(policy.check_target(&scratch.0.join("outside/new")).is_err());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn symlinks_cannot_escape_a_root() {
+        let scratch = Scratch::new();
+        let policy = scratch.policy();
+        let link = scratch.0.join("root/link");
+        std::os::unix::fs::symlink(scratch.0.join("outside"), &link).unwrap();
+
+        assert!(policy.resolve(&link).is_err());
+        assert!(policy.check_target(&link.join("new")).is_err());
+        // The link itself sits inside the root and may be removed without following it.
+        assert_eq!(policy.resolve_entry(&link).unwrap(), link);
+    }
+}
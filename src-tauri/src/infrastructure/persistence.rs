@@ -1,29 +1,192 @@
-use crate::domain::types::HistoryItem;
+use crate::domain::manifest::Manifest;
+use crate::domain::steamcmd_script::{AppId, PublishedFileId};
+use crate::domain::types::{
+    HistoryItem, InstallMarker, MoveJournal, Settings, TrashEntry, VersionSnapshot,
+    MARKER_FILE_NAME,
+};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
 use std::fs;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use tauri::{AppHandle, Manager};
 
-fn get_history_file_path(app: &AppHandle) -> Result<PathBuf, String> {
+fn get_data_file_path(app: &AppHandle, file_name: &str) -> Result<PathBuf, String> {
     let path = app.path().app_data_dir().map_err(|e| e.to_string())?;
     if !path.exists() {
         fs::create_dir_all(&path).map_err(|e| e.to_string())?;
     }
-    Ok(path.join("history.json"))
+    Ok(path.join(file_name))
 }
 
-pub fn save_history(app: &AppHandle, history: &[HistoryItem]) -> Result<(), String> {
-    let path = get_history_file_path(app)?;
-    let content = serde_json::to_string_pretty(history).map_err(|e| e.to_string())?;
+fn save_json<T: Serialize + ?Sized>(path: &Path, value: &T) -> Result<(), String> {
+    let content = serde_json::to_string_pretty(value).map_err(|e| e.to_string())?;
     fs::write(path, content).map_err(|e| e.to_string())?;
     Ok(())
 }
 
-pub fn load_history(app: &AppHandle) -> Result<Vec<HistoryItem>, String> {
-    let path = get_history_file_path(app)?;
+fn load_json<T: DeserializeOwned + Default>(path: &Path) -> Result<T, String> {
     if !path.exists() {
-        return Ok(Vec::new());
+        return Ok(T::default());
     }
     let content = fs::read_to_string(path).map_err(|e| e.to_string())?;
-    let history = serde_json::from_str(&content).map_err(|e| e.to_string())?;
-    Ok(history)
+    serde_json::from_str(&content).map_err(|e| e.to_string())
+}
+
+pub fn save_history(app: &AppHandle, history: &[HistoryItem]) -> Result<(), String> {
+    save_json(&get_data_file_path(app, "history.json")?, history)
+}
+
+pub fn load_history(app: &AppHandle) -> Result<Vec<HistoryItem>, String> {
+    load_json(&get_data_file_path(app, "history.json")?)
+}
+
+pub fn save_settings(app: &AppHandle, settings: &Settings) -> Result<(), String> {
+    save_json(&get_data_file_path(app, "settings.json")?, settings)
+}
+
+pub fn load_settings(app: &AppHandle) -> Result<Settings, String> {
+    load_json(&get_data_file_path(app, "settings.json")?)
+}
+
+pub fn save_trash(app: &AppHandle, trash: &[TrashEntry]) -> Result<(), String> {
+    save_json(&get_data_file_path(app, "trash.json")?, trash)
+}
+
+pub fn load_trash(app: &AppHandle) -> Result<Vec<TrashEntry>, String> {
+    load_json(&get_data_file_path(app, "trash.json")?)
+}
+
+fn get_item_file_path(app: &AppHandle, folder: &str, id: &str) -> Result<PathBuf, String> {
+    if id.is_empty() || !id.chars().all(|c| c.is_ascii_alphanumeric() || c == '-') {
+        return Err(format!("Invalid item id: {}", id));
+    }
+    let dir = get_data_file_path(app, folder)?;
+    fs::create_dir_all(&dir).map_err(|e| e.to_string())?;
+    Ok(dir.join(format!("{}.json", id)))
+}
+
+fn get_manifest_path(app: &AppHandle, id: &str) -> Result<PathBuf, String> {
+    get_item_file_path(app, "manifests", id)
+}
+
+pub fn save_manifest(app: &AppHandle, id: &str, manifest: &Manifest) -> Result<(), String> {
+    save_json(&get_manifest_path(app, id)?, manifest)
+}
+
+pub fn load_manifest(app: &AppHandle, id: &str) -> Result<Option<Manifest>, String> {
+    load_json(&get_manifest_path(app, id)?)
+}
+
+pub fn remove_manifest(app: &AppHandle, id: &str) -> Result<(), String> {
+    let path = get_manifest_path(app, id)?;
+    if path.exists() {
+        fs::remove_file(path).map_err(|e| e.to_string())?;
+    }
+    Ok(())
+}
+
+pub fn save_move_journal(app: &AppHandle, journal: &MoveJournal) -> Result<(), String> {
+    save_json(
+        &get_item_file_path(app, "moves", &journal.item.id)?,
+        journal,
+    )
+}
+
+/// Every journal on disk; one that cannot be parsed is returned as an error describing
+/// it, so the others are still found.
+pub fn load_move_journals(app: &AppHandle) -> Result<Vec<Result<MoveJournal, String>>, String> {
+    let dir = get_data_file_path(app, "moves")?;
+    let entries = match fs::read_dir(&dir) {
+        Ok(entries) => entries,
+        Err(_) => return Ok(Vec::new()),
+    };
+    let mut journals = Vec::new();
+    for entry in entries.flatten() {
+        let content = fs::read_to_string(entry.path()).map_err(|e| e.to_string())?;
+        journals.push(serde_json::from_str(&content).map_err(|e| {
+            format!(
+                "Skipping unreadable move journal {}: {}",
+                entry.path().display(),
+                e
+            )
+        }));
+    }
+    Ok(journals)
+}
+
+pub fn remove_move_journal(app: &AppHandle, id: &str) -> Result<(), String> {
+    let path = get_item_file_path(app, "moves", id)?;
+    if path.exists() {
+        fs::remove_file(path).map_err(|e| e.to_string())?;
+    }
+    Ok(())
+}
+
+// steamcmd's `appworkshop_<app>.acf` from an item's last download, which tells it
+// what an existing install holds so an update only fetches what changed.
+fn get_workshop_state_path(
+    app: &AppHandle,
+    app_id: AppId,
+    file_id: PublishedFileId,
+) -> Result<PathBuf, String> {
+    let dir = get_data_file_path(app, "workshop_state")?;
+    fs::create_dir_all(&dir).map_err(|e| e.to_string())?;
+    Ok(dir.join(format!("{}-{}.acf", app_id, file_id)))
+}
+
+pub fn save_workshop_state(
+    app: &AppHandle,
+    app_id: AppId,
+    file_id: PublishedFileId,
+    acf: &[u8],
+) -> Result<(), String> {
+    fs::write(get_workshop_state_path(app, app_id, file_id)?, acf).map_err(|e| e.to_string())
+}
+
+pub fn load_workshop_state(
+    app: &AppHandle,
+    app_id: AppId,
+    file_id: PublishedFileId,
+) -> Option<Vec<u8>> {
+    fs::read(get_workshop_state_path(app, app_id, file_id).ok()?).ok()
+}
+
+pub fn remove_workshop_state(
+    app: &AppHandle,
+    app_id: AppId,
+    file_id: PublishedFileId,
+) -> Result<(), String> {
+    match fs::remove_file(get_workshop_state_path(app, app_id, file_id)?) {
+        Err(e) if e.kind() != std::io::ErrorKind::NotFound => Err(e.to_string()),
+        _ => Ok(()),
+    }
+}
+
+pub fn save_version_info(versions_dir: &Path, snapshot: &VersionSnapshot) -> Result<(), String> {
+    save_json(
+        &versions_dir.join(format!("{}.json", snapshot.id)),
+        snapshot,
+    )
+}
+
+pub fn load_version_infos(versions_dir: &Path) -> Vec<VersionSnapshot> {
+    let entries = match fs::read_dir(versions_dir) {
+        Ok(entries) => entries,
+        Err(_) => return Vec::new(),
+    };
+    entries
+        .flatten()
+        .filter(|e| e.path().extension().is_some_and(|ext| ext == "json"))
+        .filter_map(|e| fs::read_to_string(e.path()).ok())
+        .filter_map(|content| serde_json::from_str(&content).ok())
+        .collect()
+}
+
+pub fn save_marker(dir: &Path, marker: &InstallMarker) -> Result<(), String> {
+    save_json(&dir.join(MARKER_FILE_NAME), marker)
+}
+
+pub fn load_marker(dir: &Path) -> Option<InstallMarker> {
+    let content = fs::read_to_string(dir.join(MARKER_FILE_NAME)).ok()?;
+    serde_json::from_str(&content).ok()
 }
@@ -1,9 +1,9 @@
 use std::process::{Child, Command, Stdio};
 use std::sync::{Arc, Mutex};
-use tauri::{AppHandle, Emitter};
 
+/// Tracks running steamcmd processes so they can be stopped when the app closes.
 pub struct ProcessManager {
-    pub processes: Arc<Mutex<Vec<Child>>>,
+    pub processes: Arc<Mutex<Vec<u32>>>,
 }
 
 impl ProcessManager {
@@ -13,7 +13,9 @@ impl ProcessManager {
         }
     }
 
-    pub fn spawn_process(&self, cmd: &mut Command, app: &AppHandle) -> Result<u32, String> {
+    /// Spawns `cmd` with piped stdio. On unix it gets its own process group, since
+    /// `steamcmd.sh` runs the real binary as a child that has to be stopped as well.
+    pub fn spawn_process(&self, cmd: &mut Command) -> Result<Child, String> {
         cmd.stdin(Stdio::piped())
             .stdout(Stdio::piped())
             .stderr(Stdio::piped());
@@ -23,37 +25,71 @@ impl ProcessManager {
             use std::os::windows::process::CommandExt;
             cmd.creation_flags(0x08000000);
         }
+        #[cfg(unix)]
+        {
+            use std::os::unix::process::CommandExt;
+            cmd.process_group(0);
+        }
 
         let child = cmd.spawn().map_err(|e| e.to_string())?;
 
-        let pid = child.id();
-        let _ = app.emit("process-spawned", pid);
-
         if let Ok(mut procs) = self.processes.lock() {
-            procs.push(child);
+            procs.push(child.id());
         } else {
             return Err("Failed to lock process list".to_string());
         }
 
-        Ok(pid)
+        Ok(child)
     }
 
     pub fn kill_all(&self) {
         if let Ok(mut procs) = self.processes.lock() {
-            for child in procs.iter_mut() {
-                let _ = child.kill();
+            for pid in procs.iter() {
+                kill_tree(*pid);
             }
             procs.clear();
         }
     }
 
+    pub fn kill(&self, pid: u32) {
+        kill_tree(pid);
+        self.remove_process(pid);
+    }
+
     pub fn remove_process(&self, pid: u32) {
         if let Ok(mut procs) = self.processes.lock() {
-            procs.retain(|p| p.id() != pid);
+            procs.retain(|p| *p != pid);
         }
     }
 }
 
+#[cfg(unix)]
+fn kill_tree(pid: u32) {
+    // A pid of 0 or one that does not fit would turn into our own group or every process.
+    let Ok(pgid) = libc::pid_t::try_from(pid) else {
+        return;
+    };
+    if pgid <= 0 {
+        return;
+    }
+    // SAFETY: kill has no memory-safety preconditions. `pgid` is positive, so the
+    // negated value names exactly one process group. It is the id of a child spawned
+    // with `process_group(0)`, which made it the leader of that group. Pids are only
+    // tracked until the child is reaped, so the id cannot have been reused.
+    unsafe {
+        libc::kill(-pgid, libc::SIGTERM);
+    }
+}
+
+#[cfg(target_os = "windows")]
+fn kill_tree(pid: u32) {
+    use std::os::windows::process::CommandExt;
+    let _ = Command::new("taskkill")
+        .args(["/PID", &pid.to_string(), "/T", "/F"])
+        .creation_flags(0x08000000)
+        .status();
+}
+
 impl Default for ProcessManager {
     fn default() -> Self {
         Self::new()
@@ -1,31 +1,58 @@
-use crate::infrastructure::bin_loader::get_steamcmd_path;
+use crate::domain::steamcmd_output::{
+    classify_error, engine_state, is_logged_in, login_prompt, EngineStart, LoginPrompt,
+    OutputSummary,
+};
+use crate::domain::steamcmd_script::SteamCmdScript;
+use crate::domain::types::{DownloadFailure, FailureClass, SteamGuardState};
+use crate::infrastructure::bin_loader::{get_steamcmd_path, set_engine_state};
 use crate::infrastructure::process_manager::ProcessManager;
-use std::io::{BufRead, BufReader, Write};
-use std::process::Stdio;
-use std::sync::mpsc;
+use std::io::{Read, Write};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{mpsc, Arc, Mutex};
 use std::thread;
-use tauri::{AppHandle, Emitter};
+use std::time::Duration;
+use tauri::{AppHandle, Emitter, Manager};
+
+/// How long steamcmd is left waiting for a Steam Guard code before the login is given up.
+const GUARD_CODE_TIMEOUT: Duration = Duration::from_secs(300);
+/// How long an account login may go without a prompt or a result before steamcmd is
+/// stopped.
+const LOGIN_TIMEOUT: Duration = Duration::from_secs(120);
+
+pub struct SteamCmdResult {
+    pub success: bool,
+    /// Ended by a fault rather than by exiting or being stopped.
+    pub crashed: bool,
+    pub summary: OutputSummary,
+}
+
+impl SteamCmdResult {
+    pub fn engine_start(&self) -> EngineStart {
+        if self.success {
+            EngineStart::Started
+        } else {
+            self.summary.engine_start(self.crashed)
+        }
+    }
+}
+
+/// A running steamcmd. `started` receives once steamcmd has updated itself and loaded
+/// the Steam API, and closes without a message if it exits before that.
+pub struct SteamCmdRun {
+    pub started: mpsc::Receiver<()>,
+    pub result: mpsc::Receiver<SteamCmdResult>,
+}
 
 pub fn execute_steamcmd_with_progress(
     app: &AppHandle,
-    commands: Vec<String>,
+    script: &SteamCmdScript,
     process_manager: &ProcessManager,
     item_id: String,
-) -> Result<mpsc::Receiver<bool>, String> {
+) -> Result<SteamCmdRun, String> {
     let steamcmd_path = get_steamcmd_path(app)?;
 
     let mut cmd = std::process::Command::new(&steamcmd_path);
-    cmd.stdin(Stdio::piped())
-        .stdout(Stdio::piped())
-        .stderr(Stdio::piped());
-
-    #[cfg(target_os = "windows")]
-    {
-        use std::os::windows::process::CommandExt;
-        cmd.creation_flags(0x08000000);
-    }
-
-    let mut child = cmd.spawn().map_err(|e| e.to_string())?;
+    let mut child = process_manager.spawn_process(&mut cmd)?;
 
     let pid = child.id();
     let _ = app.emit("process-spawned", pid);
@@ -34,46 +61,76 @@ pub fn execute_steamcmd_with_progress(
     let stdout = child.stdout.take().ok_or("Failed to capture stdout")?;
     let stderr = child.stderr.take().ok_or("Failed to capture stderr")?;
 
+    // stdin stays open until the output ends, since an account login is answered and
+    // continued from what steamcmd prints.
+    let (stdin_tx, stdin_rx) = mpsc::channel::<String>();
     thread::spawn(move || {
         let mut writer = stdin;
-        for command in commands {
+        for command in stdin_rx {
             let _ = writeln!(writer, "{}", command);
             let _ = writer.flush();
         }
-        let _ = writeln!(writer, "quit");
-        let _ = writer.flush();
-        drop(writer);
+    });
+    let (first, rest) = script.render_stdin_stages();
+    for command in first {
+        let _ = stdin_tx.send(command);
+    }
+    let login_timed_out = Arc::new(AtomicBool::new(false));
+    let mut login = script.uses_account().then(|| AccountLogin {
+        rest,
+        password: script.password().map(str::to_string),
+        password_sent: false,
+        watchdog: watch_login(
+            app,
+            pid,
+            process_manager.processes.clone(),
+            login_timed_out.clone(),
+        ),
     });
 
+    let (started_tx, started) = mpsc::channel();
     let app_out = app.clone();
     let item_id_out = item_id.clone();
-    thread::spawn(move || {
-        let reader = BufReader::new(stdout);
-        for line in reader.lines() {
-            if let Ok(l) = line {
-                let _ = app_out.emit("terminal-output", l.clone());
-
-                if let Some(progress) = parse_progress(&l) {
-                    let _ = app_out.emit(
-                        "download-progress",
-                        serde_json::json!({
-                            "id": item_id_out,
-                            "progress": progress
-                        }),
-                    );
+    let stdout_handle = thread::spawn(move || {
+        let mut started_tx = Some(started_tx);
+        let mut summary = OutputSummary::default();
+        read_output(stdout, |l| {
+            let _ = app_out.emit("terminal-output", l.to_string());
+            if let Some(state) = engine_state(l) {
+                set_engine_state(&app_out, state);
+            }
+            if let Some(account) = login.as_mut() {
+                if !account.observe(&app_out, &item_id_out, l, &stdin_tx) {
+                    login = None;
                 }
             }
-        }
+            summary.observe(l);
+            if summary.started {
+                if let Some(tx) = started_tx.take() {
+                    let _ = tx.send(());
+                }
+            }
+
+            if let Some(progress) = parse_progress(l) {
+                let _ = app_out.emit(
+                    "download-progress",
+                    serde_json::json!({
+                        "id": item_id_out,
+                        "progress": progress
+                    }),
+                );
+            }
+        });
+        summary
     });
 
     let app_err = app.clone();
+    // Read like stdout, so a line that is not valid UTF-8 cannot stop the pipe being
+    // drained and stall steamcmd.
     thread::spawn(move || {
-        let reader = BufReader::new(stderr);
-        for line in reader.lines() {
-            if let Ok(l) = line {
-                let _ = app_err.emit("terminal-output", l);
-            }
-        }
+        read_output(stderr, |l| {
+            let _ = app_err.emit("terminal-output", l.to_string());
+        });
     });
 
     let (tx, rx) = mpsc::channel();
@@ -81,14 +138,202 @@ pub fn execute_steamcmd_with_progress(
     let processes = process_manager.processes.clone();
     
     thread::spawn(move || {
-        let success = child.wait().map(|s| s.success()).unwrap_or(false);
+        let status = child.wait().ok();
+        let exited_ok = status.is_some_and(|s| s.success());
+        let crashed = status.is_some_and(is_crash);
         if let Ok(mut procs) = processes.lock() {
-            procs.retain(|p| p.id() != pid);
+            procs.retain(|p| *p != pid);
+        }
+        let mut summary = stdout_handle.join().unwrap_or_default();
+        if login_timed_out.load(Ordering::Relaxed) {
+            summary.failure = Some(DownloadFailure::new(
+                FailureClass::Login,
+                "The Steam login did not finish in time",
+            ));
         }
-        let _ = tx.send(success);
+        let _ = tx.send(SteamCmdResult {
+            success: exited_ok && summary.failure.is_none(),
+            crashed,
+            summary,
+        });
     });
 
-    Ok(rx)
+    Ok(SteamCmdRun {
+        started,
+        result: rx,
+    })
+}
+
+// Stopping steamcmd (SIGTERM, or taskkill's exit code 1) is not a crash.
+#[cfg(unix)]
+fn is_crash(status: std::process::ExitStatus) -> bool {
+    use std::os::unix::process::ExitStatusExt;
+    status.signal().is_some_and(|signal| {
+        [libc::SIGSEGV, libc::SIGBUS, libc::SIGILL, libc::SIGFPE, libc::SIGABRT].contains(&signal)
+    })
+}
+
+// NTSTATUS error codes such as 0xC0000005, an access violation.
+#[cfg(windows)]
+fn is_crash(status: std::process::ExitStatus) -> bool {
+    status.code().is_some_and(|code| code as u32 >= 0xC000_0000)
+}
+
+/// Calls `on_line` for each line of output, and for a login prompt that steamcmd
+/// printed without a line break and is now waiting on.
+fn read_output(mut stdout: impl Read, mut on_line: impl FnMut(&str)) {
+    let mut buf = [0u8; 4096];
+    let mut pending = Vec::new();
+    loop {
+        let n = match stdout.read(&mut buf) {
+            Ok(0) | Err(_) => break,
+            Ok(n) => n,
+        };
+        pending.extend_from_slice(&buf[..n]);
+        while let Some(end) = pending.iter().position(|b| *b == b'\n') {
+            let line: Vec<u8> = pending.drain(..=end).collect();
+            on_line(String::from_utf8_lossy(&line).trim_end_matches(['\r', '\n']));
+        }
+        if !pending.is_empty() {
+            let partial = String::from_utf8_lossy(&pending).into_owned();
+            if login_prompt(&partial).is_some() {
+                pending.clear();
+                on_line(&partial);
+            }
+        }
+    }
+    if !pending.is_empty() {
+        on_line(&String::from_utf8_lossy(&pending));
+    }
+}
+
+/// Answers steamcmd's prompts during an account login, then sends the rest of the
+/// script once it has logged in.
+struct AccountLogin {
+    rest: Vec<String>,
+    password: Option<String>,
+    password_sent: bool,
+    /// Gets the time allowed until the next step; dropped when the login is over.
+    watchdog: mpsc::Sender<Duration>,
+}
+
+impl AccountLogin {
+    /// Returns false once the login is over, either way.
+    fn observe(
+        &mut self,
+        app: &AppHandle,
+        item_id: &str,
+        line: &str,
+        stdin: &mpsc::Sender<String>,
+    ) -> bool {
+        match login_prompt(line) {
+            // Only asked when steamcmd has no cached login for the account. An empty
+            // answer fails the login instead of leaving steamcmd waiting.
+            Some(LoginPrompt::Password) => {
+                let password = if self.password_sent {
+                    None
+                } else {
+                    self.password.take()
+                };
+                if password.is_none() {
+                    let _ = app.emit(
+                        "terminal-output",
+                        "No saved password for the Steam account".to_string(),
+                    );
+                }
+                self.password_sent = true;
+                let _ = stdin.send(password.unwrap_or_default());
+                let _ = self.watchdog.send(LOGIN_TIMEOUT);
+            }
+            Some(prompt) => {
+                ask_guard_code(app, item_id, prompt, stdin.clone());
+                let _ = self.watchdog.send(GUARD_CODE_TIMEOUT + LOGIN_TIMEOUT);
+            }
+            None if is_logged_in(line) => {
+                for command in self.rest.drain(..) {
+                    let _ = stdin.send(command);
+                }
+                return false;
+            }
+            // Whatever went wrong, steamcmd would otherwise sit at its prompt with
+            // nothing left to run.
+            None if classify_error(line).is_some() => {
+                let _ = stdin.send("quit".to_string());
+                return false;
+            }
+            None => {}
+        }
+        true
+    }
+}
+
+/// Stops steamcmd when a login stalls: once started, each step has to arrive within
+/// the time last sent on the returned channel.
+fn watch_login(
+    app: &AppHandle,
+    pid: u32,
+    processes: Arc<Mutex<Vec<u32>>>,
+    timed_out: Arc<AtomicBool>,
+) -> mpsc::Sender<Duration> {
+    let (tx, rx) = mpsc::channel();
+    let app = app.clone();
+    thread::spawn(move || {
+        let mut timeout = LOGIN_TIMEOUT;
+        loop {
+            match rx.recv_timeout(timeout) {
+                Ok(next) => timeout = next,
+                Err(mpsc::RecvTimeoutError::Disconnected) => return,
+                Err(mpsc::RecvTimeoutError::Timeout) => break,
+            }
+        }
+        timed_out.store(true, Ordering::Relaxed);
+        let _ = app.emit(
+            "terminal-output",
+            "The Steam login did not finish in time, stopping steamcmd".to_string(),
+        );
+        ProcessManager { processes }.kill(pid);
+    });
+    tx
+}
+
+fn ask_guard_code(
+    app: &AppHandle,
+    item_id: &str,
+    prompt: LoginPrompt,
+    stdin: mpsc::Sender<String>,
+) {
+    let (tx, rx) = mpsc::channel();
+    if let Some(state) = app.try_state::<SteamGuardState>() {
+        if let Ok(mut pending) = state.0.lock() {
+            *pending = Some(tx);
+        }
+    }
+    let kind = match prompt {
+        LoginPrompt::TwoFactorCode => "two-factor",
+        _ => "email",
+    };
+    let _ = app.emit(
+        "steam-guard-required",
+        serde_json::json!({
+            "id": item_id,
+            "kind": kind
+        }),
+    );
+
+    let app = app.clone();
+    let item_id = item_id.to_string();
+    thread::spawn(move || {
+        let code = rx.recv_timeout(GUARD_CODE_TIMEOUT).unwrap_or_else(|_| {
+            if let Some(state) = app.try_state::<SteamGuardState>() {
+                if let Ok(mut pending) = state.0.lock() {
+                    *pending = None;
+                }
+            }
+            let _ = app.emit("steam-guard-expired", serde_json::json!({ "id": item_id }));
+            String::new()
+        });
+        let _ = stdin.send(code);
+    });
 }
 
 fn parse_progress(line: &str) -> Option<f32> {
@@ -0,0 +1,22 @@
+use crate::domain::parser::{parse_published_file_details, WorkshopDetails};
+use crate::domain::steamcmd_script::PublishedFileId;
+
+const DETAILS_URL: &str =
+    "https://api.steampowered.com/ISteamRemoteStorage/GetPublishedFileDetails/v1/";
+
+pub fn fetch_details(file_id: PublishedFileId) -> Result<WorkshopDetails, String> {
+    let body = format!("itemcount=1&publishedfileids%5B0%5D={}", file_id);
+    let output = std::process::Command::new("curl")
+        .args([
+            "-s",
+            "-L",
+            "-A",
+            "Mozilla/5.0",
+            "--data",
+            &body,
+            DETAILS_URL,
+        ])
+        .output()
+        .map_err(|e| e.to_string())?;
+    parse_published_file_details(&String::from_utf8_lossy(&output.stdout))
+}
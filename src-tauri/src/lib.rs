@@ -3,11 +3,22 @@ pub mod domain;
 pub mod infrastructure;
 
 use crate::application::commands::{
-    add_download, check_path_exists, clear_history, get_history, get_queue, open_folder, remove_history_item, retry_download, AppState,
+    add_download, adopt_orphan, check_path_exists, check_steamcmd, clean_temp_folders, clear_history, dedupe_library,
+    delete_orphan, deploy_item, detect_steamcmd, empty_trash, export_items, get_account, get_engine_state, get_history,
+    get_queue, get_settings, get_startup_report, get_trash, list_gma, list_versions, open_folder, prepare_engine,
+    reconcile_library, remove_account, remove_history_item, restore_history_item, retry_download, rollback_item,
+    save_account, scan_temp_folders, set_keep_versions, submit_guard_code, undeploy_item, update_settings, verify_item, verify_library,
+    AppState,
 };
 use crate::application::history_service::init_history;
-use crate::application::queue_manager::start_worker;
-use crate::domain::types::{HistoryState, QueueState, ProcessManagerState};
+use crate::application::queue_manager::{resume_moves, start_worker};
+use crate::application::settings_service::init_settings;
+use crate::application::startup_service::{report_problem, scan_leftovers};
+use crate::application::trash_service::init_trash;
+use crate::domain::types::{
+    EngineStatus, HistoryState, QueueState, ProcessManagerState, SettingsState, StartupState, SteamGuardState,
+    TrashState,
+};
 use crate::infrastructure::process_manager::ProcessManager;
 use std::sync::{Arc, Mutex};
 use tauri::Manager;
@@ -16,15 +27,32 @@ use tauri::Manager;
 pub fn run() {
     tauri::Builder::default()
         .plugin(tauri_plugin_opener::init())
+        .plugin(tauri_plugin_dialog::init())
         .manage(AppState(Mutex::new(QueueState::new())))
         .manage(HistoryState::default())
+        .manage(SettingsState::default())
+        .manage(TrashState::default())
+        .manage(EngineStatus::default())
+        .manage(SteamGuardState::default())
+        .manage(StartupState::default())
         .manage(ProcessManagerState(Arc::new(Mutex::new(ProcessManager::new()))))
         .setup(|app| {
+            let settings_state = app.state::<SettingsState>();
+            if let Err(e) = init_settings(app.handle(), &settings_state) {
+                report_problem(app.handle(), format!("Failed to init settings: {}", e));
+            }
             start_worker(app.handle().clone());
             let history_state = app.state::<HistoryState>();
             if let Err(e) = init_history(app.handle(), &history_state) {
-                eprintln!("Failed to init history: {}", e);
+                report_problem(app.handle(), format!("Failed to init history: {}", e));
+            }
+            resume_moves(app.handle());
+            let retention_days = settings_state.0.lock().map(|s| s.trash_retention_days).unwrap_or(7);
+            let trash_state = app.state::<TrashState>();
+            if let Err(e) = init_trash(app.handle(), &trash_state, retention_days) {
+                report_problem(app.handle(), format!("Failed to init trash: {}", e));
             }
+            scan_leftovers(app.handle());
             Ok(())
         })
         .on_window_event(|window, event| {
@@ -44,7 +72,36 @@ pub fn run() {
             clear_history,
             remove_history_item,
             check_path_exists,
-            retry_download
+            retry_download,
+            reconcile_library,
+            adopt_orphan,
+            delete_orphan,
+            get_trash,
+            restore_history_item,
+            empty_trash,
+            get_settings,
+            update_settings,
+            deploy_item,
+            undeploy_item,
+            verify_item,
+            verify_library,
+            export_items,
+            scan_temp_folders,
+            get_startup_report,
+            clean_temp_folders,
+            list_versions,
+            rollback_item,
+            set_keep_versions,
+            dedupe_library,
+            list_gma,
+            detect_steamcmd,
+            check_steamcmd,
+            prepare_engine,
+            get_engine_state,
+            get_account,
+            save_account,
+            remove_account,
+            submit_guard_code
         ])
         .run(tauri::generate_context!())
         .expect("error while running tauri application");
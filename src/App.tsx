@@ -1,12 +1,44 @@
-import { useState } from "react";
+import { useEffect, useState } from "react";
+import { invoke } from "@tauri-apps/api/core";
+import { listen } from "@tauri-apps/api/event";
 import "./App.css";
 import { DownloadTab } from "./components/DownloadTab";
 import { HistoryTab } from "./components/HistoryTab";
+import { SettingsTab } from "./components/SettingsTab";
+import { SteamGuardPrompt } from "./components/SteamGuardPrompt";
+import { EngineState, StartupReport, TempFolder, engineLabel } from "./types";
 
-type Tab = "download" | "history";
+type Tab = "download" | "history" | "settings";
 
 function App() {
   const [activeTab, setActiveTab] = useState<Tab>("download");
+  const [engine, setEngine] = useState<EngineState>("Unprepared");
+  const [leftovers, setLeftovers] = useState<TempFolder[]>([]);
+  const [problems, setProblems] = useState<string[]>([]);
+  const [seenProblems, setSeenProblems] = useState(0);
+
+  useEffect(() => {
+    const unlisten = listen<EngineState>("engine-state", (event) => setEngine(event.payload));
+    invoke<EngineState>("get_engine_state").then(setEngine).catch(console.error);
+    invoke("prepare_engine").catch(console.error);
+    // Startup runs before this window listens, so what it found is fetched here and
+    // fetched again whenever something is added later, e.g. by a resumed move.
+    const fetchReport = (withLeftovers: boolean) =>
+      invoke<StartupReport>("get_startup_report")
+        .then((report) => {
+          setProblems(report.problems);
+          if (withLeftovers) setLeftovers(report.temp_folders);
+        })
+        .catch(console.error);
+    fetchReport(true);
+    const unlistenReport = listen("startup-report", () => fetchReport(false));
+    return () => {
+      unlisten.then(f => f());
+      unlistenReport.then(f => f());
+    };
+  }, []);
+
+  const engineText = engineLabel(engine) ?? (typeof engine === "object" && "Failed" in engine ? "steamcmd failed" : null);
 
   return (
     <div className="container">
@@ -38,22 +70,69 @@ function App() {
                     backgroundColor: activeTab === "history" ? "var(--primary)" : "transparent",
                     color: activeTab === "history" ? "var(--primary-foreground)" : "var(--muted-foreground)" 
                 }}
-                onClick={() => setActiveTab("history")}
+                onClick={() => { setActiveTab("history"); setLeftovers([]); }}
              >
                History
              </button>
+             <button
+                className={`btn tab-btn`}
+                style={{ 
+                    height: "2rem", 
+                    backgroundColor: activeTab === "settings" ? "var(--primary)" : "transparent",
+                    color: activeTab === "settings" ? "var(--primary-foreground)" : "var(--muted-foreground)" 
+                }}
+                onClick={() => setActiveTab("settings")}
+             >
+               Settings
+             </button>
            </div>
 
+           {leftovers.length > 0 && (
+             <button
+               className="btn"
+               title="Unfinished downloads left in the staging folder; review them in History"
+               onClick={() => { setActiveTab("history"); setLeftovers([]); }}
+               style={{ height: "auto", fontSize: "0.75rem", padding: "4px 8px", background: "var(--muted)", borderRadius: "var(--radius)", color: "var(--muted-foreground)", border: "none" }}
+             >
+               {leftovers.length} leftover download{leftovers.length === 1 ? "" : "s"}
+             </button>
+           )}
+
+           {engineText && (
+             <span
+               title={typeof engine === "object" && "Failed" in engine ? engine.Failed : undefined}
+               style={{ fontSize: "0.75rem", padding: "4px 8px", background: "var(--muted)", borderRadius: "var(--radius)", color: "var(--muted-foreground)" }}
+             >
+               {engineText}
+             </span>
+           )}
+
            <span className="version-badge" style={{ fontSize: "0.75rem", padding: "4px 8px", background: "var(--muted)", borderRadius: "var(--radius)", color: "var(--muted-foreground)" }}>
              v0.1.1
            </span>
         </div>
       </header>
 
+      {problems.length > seenProblems && (
+        <div className="card" style={{ padding: "8px 12px", fontSize: "0.8rem", display: "flex", justifyContent: "space-between", alignItems: "flex-start", gap: "8px" }}>
+          <div style={{ color: "var(--flexoki-red-primary)" }}>
+            {problems.slice(seenProblems).map((problem, i) => (
+              <div key={i}>{problem}</div>
+            ))}
+          </div>
+          <button className="btn" style={{ height: "1.75rem", padding: "0 0.5rem", border: "1px solid var(--border)" }} onClick={() => setSeenProblems(problems.length)}>
+            Dismiss
+          </button>
+        </div>
+      )}
+
       <div style={{ flex: 1, minHeight: 0, overflow: "hidden" }}>
         {activeTab === "download" && <DownloadTab />}
         {activeTab === "history" && <HistoryTab />}
+        {activeTab === "settings" && <SettingsTab />}
       </div>
+
+      <SteamGuardPrompt />
     </div>
   );
 }
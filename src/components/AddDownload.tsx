@@ -1,9 +1,13 @@
 import { useState } from "react";
 import { invoke } from "@tauri-apps/api/core";
+import { ExtractMode } from "../types";
 
 export function AddDownload() {
     const [input, setInput] = useState("");
     const [name, setName] = useState("");
+    const [destination, setDestination] = useState("");
+    const [flatten, setFlatten] = useState(false);
+    const [extract, setExtract] = useState<ExtractMode | "">("");
     const [error, setError] = useState<string | null>(null);
 
     const handleAdd = async () => {
@@ -12,9 +16,12 @@ export function AddDownload() {
 
         try {
             const displayName = name || `App ${input}`;
-            await invoke("add_download", { steamId: input, name: displayName });
+            await invoke("add_download", { steamId: input, name: displayName, destination: destination || null, flatten: flatten || null, extract: extract || null });
             setInput("");
             setName("");
+            setDestination("");
+            setFlatten(false);
+            setExtract("");
         } catch (e) {
             console.error("Failed to add download:", e);
             setError(String(e));
@@ -49,6 +56,32 @@ export function AddDownload() {
                     />
                 </div>
 
+                <div style={{ display: "flex", flexDirection: "column", gap: "0.5rem" }}>
+                    <label style={{ fontSize: "0.875rem", fontWeight: 500 }}>Destination Folder (Optional)</label>
+                    <input
+                        className="input"
+                        type="text"
+                        placeholder="Defaults to the configured root"
+                        value={destination}
+                        onChange={(e) => setDestination(e.target.value)}
+                    />
+                </div>
+
+                <label style={{ display: "flex", alignItems: "center", gap: "0.5rem", fontSize: "0.875rem" }}>
+                    <input type="checkbox" checked={flatten} onChange={(e) => setFlatten(e.target.checked)} />
+                    Flatten wrapper folders
+                </label>
+
+                <div style={{ display: "flex", flexDirection: "column", gap: "0.5rem" }}>
+                    <label style={{ fontSize: "0.875rem", fontWeight: 500 }}>Extract archives (zip, tar, gma)</label>
+                    <select className="input" value={extract} onChange={(e) => setExtract(e.target.value as ExtractMode | "")}>
+                        <option value="">App default</option>
+                        <option value="Off">Off</option>
+                        <option value="KeepArchive">Extract, keep archive</option>
+                        <option value="DeleteArchive">Extract, delete archive</option>
+                    </select>
+                </div>
+
                 <button className="btn btn-primary" onClick={handleAdd} style={{ marginTop: "0.5rem", width: "100%" }}>
                     Add to Queue
                 </button>
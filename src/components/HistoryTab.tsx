@@ -1,21 +1,55 @@
 import { useEffect, useState } from "react";
 import { invoke } from "@tauri-apps/api/core";
-import { HistoryItem } from "../types";
+import { listen } from "@tauri-apps/api/event";
+import { save } from "@tauri-apps/plugin-dialog";
+import { ArchiveFormat, DedupeReport, HistoryItem, LibraryReport, TempFolder, TrashEntry, VerifyReport, VersionSnapshot } from "../types";
 
 interface HistoryRowProps {
     item: HistoryItem;
+    exists: boolean;
     onDelete: (id: string) => void;
+    onToggleDeploy: (item: HistoryItem) => void;
+    onVerify: (id: string) => void;
+    onExport: (ids: string[]) => void;
     onRetry: (steamId: string, name: string) => void;
+    onChanged: () => void;
 }
 
-function HistoryRow({ item, onDelete, onRetry }: HistoryRowProps) {
-    const [exists, setExists] = useState(false);
+function HistoryRow({ item, exists, onDelete, onToggleDeploy, onVerify, onExport, onRetry, onChanged }: HistoryRowProps) {
+    const failure = typeof item.outcome === "object" ? item.outcome.Failed : null;
+    const [versions, setVersions] = useState<VersionSnapshot[] | null>(null);
 
-    useEffect(() => {
-        invoke<boolean>("check_path_exists", { path: item.install_path })
-            .then(setExists)
-            .catch(() => setExists(false));
-    }, [item.install_path]);
+    const toggleVersions = async () => {
+        if (versions) {
+            setVersions(null);
+            return;
+        }
+        try {
+            setVersions(await invoke<VersionSnapshot[]>("list_versions", { id: item.id }));
+        } catch (e) {
+            alert(`Failed to list versions: ${e}`);
+        }
+    };
+
+    const handleRollback = async (versionId: string) => {
+        if (!confirm("Replace the installed files with this version? The current files are kept as a version.")) return;
+        try {
+            await invoke("rollback_item", { id: item.id, versionId });
+            setVersions(await invoke<VersionSnapshot[]>("list_versions", { id: item.id }));
+            onChanged();
+        } catch (e) {
+            alert(`Rollback failed: ${e}`);
+        }
+    };
+
+    const handleKeepVersions = async (value: string) => {
+        try {
+            await invoke("set_keep_versions", { id: item.id, keep: value === "" ? null : Number(value) });
+            onChanged();
+        } catch (e) {
+            alert(`Failed to save: ${e}`);
+        }
+    };
 
     const handleOpen = () => {
         invoke("open_folder", { path: item.install_path });
@@ -26,19 +60,67 @@ function HistoryRow({ item, onDelete, onRetry }: HistoryRowProps) {
     };
 
     return (
+        <>
         <tr style={{ borderBottom: "1px solid var(--border)" }}>
-            <td style={{ padding: "12px 16px" }}>{item.name}</td>
+            <td style={{ padding: "12px 16px" }}>
+                {item.name}
+                {item.folder_name && item.folder_name !== item.name && (
+                    <div style={{ fontSize: "0.75rem", color: "var(--muted-foreground)" }} title={item.install_path}>
+                        {item.folder_name}
+                    </div>
+                )}
+                {failure && (
+                    <div style={{ fontSize: "0.75rem", color: "var(--destructive)" }} title={failure.message}>
+                        Failed ({failure.class})
+                    </div>
+                )}
+            </td>
+            <td style={{ padding: "12px 16px" }}>{failure ? "-" : `${formatBytes(item.size_bytes)} · ${item.file_count} files`}</td>
             <td style={{ padding: "12px 16px" }}>{formatDate(item.timestamp)}</td>
             <td style={{ padding: "12px 16px", display: "flex", gap: "8px", justifyContent: "flex-end" }}>
                 {exists ? (
-                    <button
-                        className="btn"
-                        style={{ height: "2rem", padding: "0 0.5rem", background: "transparent", border: "1px solid var(--border)" }}
-                        onClick={handleOpen}
-                        title="Open Folder"
-                    >
-                        📂
-                    </button>
+                    <>
+                        <button
+                            className="btn"
+                            style={{ height: "2rem", padding: "0 0.5rem", background: "transparent", border: "1px solid var(--border)" }}
+                            onClick={handleOpen}
+                            title="Open Folder"
+                        >
+                            📂
+                        </button>
+                        <button
+                            className="btn"
+                            style={{ height: "2rem", padding: "0 0.5rem", background: "transparent", border: "1px solid var(--border)" }}
+                            onClick={() => onToggleDeploy(item)}
+                            title={item.deployments.length > 0 ? item.deployments.map((d) => d.path).join("\n") : "Deploy to the game's mod folder"}
+                        >
+                            {item.deployments.length > 0 ? "Undeploy" : "Deploy"}
+                        </button>
+                        <button
+                            className="btn"
+                            style={{ height: "2rem", padding: "0 0.5rem", background: "transparent", border: "1px solid var(--border)" }}
+                            onClick={() => onVerify(item.id)}
+                            title="Verify files"
+                        >
+                            ✔️
+                        </button>
+                        <button
+                            className="btn"
+                            style={{ height: "2rem", padding: "0 0.5rem", background: "transparent", border: "1px solid var(--border)" }}
+                            onClick={() => onExport([item.id])}
+                            title="Export as archive"
+                        >
+                            📦
+                        </button>
+                        <button
+                            className="btn"
+                            style={{ height: "2rem", padding: "0 0.5rem", background: versions ? "var(--accent)" : "transparent", border: "1px solid var(--border)" }}
+                            onClick={toggleVersions}
+                            title="Previous versions"
+                        >
+                            🕘
+                        </button>
+                    </>
                 ) : (
                     <button
                         className="btn"
@@ -59,9 +141,58 @@ function HistoryRow({ item, onDelete, onRetry }: HistoryRowProps) {
                 </button>
             </td>
         </tr>
+        {versions && (
+            <tr style={{ borderBottom: "1px solid var(--border)", background: "var(--card)" }}>
+                <td colSpan={4} style={{ padding: "8px 16px", fontSize: "0.8rem" }}>
+                    <div style={{ display: "flex", alignItems: "center", gap: "8px", marginBottom: "8px" }}>
+                        <span style={{ color: "var(--muted-foreground)" }}>Versions to keep</span>
+                        <input
+                            type="number"
+                            min={0}
+                            placeholder="default"
+                            defaultValue={item.keep_versions ?? ""}
+                            onBlur={(e) => handleKeepVersions(e.target.value)}
+                            style={{ width: "5rem" }}
+                        />
+                    </div>
+                    {versions.length === 0 ? (
+                        <div style={{ color: "var(--muted-foreground)" }}>No previous versions kept.</div>
+                    ) : (
+                        versions.map((v) => (
+                            <div key={v.id} style={{ display: "flex", justifyContent: "space-between", alignItems: "center", padding: "4px 0" }}>
+                                <span>
+                                    {formatDate(v.time_updated ?? v.created_at)} · {formatBytes(v.size_bytes)} · {v.file_count} files
+                                </span>
+                                <button
+                                    className="btn"
+                                    style={{ height: "1.75rem", padding: "0 0.5rem", border: "1px solid var(--border)" }}
+                                    onClick={() => handleRollback(v.id)}
+                                >
+                                    Rollback
+                                </button>
+                            </div>
+                        ))
+                    )}
+                </td>
+            </tr>
+        )}
+        </>
     );
 }
 
+export function formatBytes(bytes: number) {
+    if (!bytes) return "0 B";
+    const units = ["B", "KB", "MB", "GB", "TB"];
+    const i = Math.min(Math.floor(Math.log(bytes) / Math.log(1024)), units.length - 1);
+    return `${(bytes / Math.pow(1024, i)).toFixed(i === 0 ? 0 : 1)} ${units[i]}`;
+}
+
+function describeReport(report: VerifyReport) {
+    const problems = report.missing.length + report.modified.length + report.extra.length;
+    if (problems === 0) return "All files match the manifest.";
+    return `${report.missing.length} missing, ${report.modified.length} modified, ${report.extra.length} extra`;
+}
+
 function formatDate(timestamp: number) {
     if (!timestamp || timestamp === 0) return "Unknown";
     try {
@@ -73,26 +204,194 @@ function formatDate(timestamp: number) {
 
 export function HistoryTab() {
     const [history, setHistory] = useState<HistoryItem[]>([]);
+    const [report, setReport] = useState<LibraryReport>({ missing: [], orphans: [] });
+    const [trash, setTrash] = useState<TrashEntry[]>([]);
+    const [tempFolders, setTempFolders] = useState<TempFolder[]>([]);
+    const [verifyStatus, setVerifyStatus] = useState<string | null>(null);
+    // Steam IDs typed in for untracked folders, by path.
+    const [adoptIds, setAdoptIds] = useState<Record<string, string>>({});
+    // Item whose removal waits for the keep files / move to trash / cancel choice.
+    const [pendingDelete, setPendingDelete] = useState<HistoryItem | null>(null);
+    const [exportFormat, setExportFormat] = useState<ArchiveFormat>("Zip");
 
     const fetchHistory = async () => {
         try {
             const items = await invoke<HistoryItem[]>("get_history");
             setHistory(items);
+            setReport(await invoke<LibraryReport>("reconcile_library"));
+            setTrash(await invoke<TrashEntry[]>("get_trash"));
+            setTempFolders(await invoke<TempFolder[]>("scan_temp_folders"));
         } catch (e) {
             console.error("Failed to fetch history:", e);
         }
     };
 
+    const handleAdopt = async (path: string) => {
+        const steamId = adoptIds[path]?.trim() || null;
+        try {
+            await invoke("adopt_orphan", { path, steamId });
+            setAdoptIds((ids) => {
+                const next = { ...ids };
+                delete next[path];
+                return next;
+            });
+            fetchHistory();
+        } catch (e) {
+            alert(`Adopt failed: ${e}`);
+        }
+    };
+
+    const handleDeleteOrphan = async (path: string) => {
+        try {
+            await invoke("delete_orphan", { path });
+            fetchHistory();
+        } catch (e) {
+            alert(`Delete failed: ${e}`);
+        }
+    };
+
+    const handleRequeueTemp = async (folder: TempFolder) => {
+        if (!folder.steam_id) return;
+        const known = history.find((h) => h.steam_id === folder.steam_id);
+        try {
+            await invoke("add_download", { steamId: folder.steam_id, name: known?.name ?? `Item ${folder.content_id}`, destination: null });
+            fetchHistory();
+        } catch (e) {
+            console.error("Failed to re-queue download:", e);
+        }
+    };
+
+    const handleCleanTemp = async (paths: string[] | null) => {
+        try {
+            const freed = await invoke<number>("clean_temp_folders", { paths });
+            setVerifyStatus(`Freed ${formatBytes(freed)}`);
+            fetchHistory();
+        } catch (e) {
+            console.error("Failed to clean download folders:", e);
+        }
+    };
+
     useEffect(() => {
         fetchHistory();
+        const unlistenProgress = listen<{ name: string; current: number; total: number }>("verify-progress", (event) => {
+            setVerifyStatus(`Verifying ${event.payload.current}/${event.payload.total}: ${event.payload.name}`);
+        });
+        const unlistenComplete = listen<VerifyReport[]>("verify-complete", (event) => {
+            const damaged = event.payload.filter((r) => r.missing.length + r.modified.length + r.extra.length > 0);
+            setVerifyStatus(`Verified ${event.payload.length} items, ${damaged.length} with problems.`);
+        });
+        const unlistenExport = listen<{ done: number; total: number }>("export-progress", (event) => {
+            const { done, total } = event.payload;
+            setVerifyStatus(`Exporting ${formatBytes(done)} / ${formatBytes(total)}`);
+        });
+        const unlistenExportDone = listen<{ output: string }>("export-complete", (event) => {
+            setVerifyStatus(`Exported to ${event.payload.output}`);
+        });
+        const unlistenExportFailed = listen<{ error: string }>("export-failed", (event) => {
+            setVerifyStatus(`Export failed: ${event.payload.error}`);
+        });
+        const unlistenDedupe = listen<{ name: string; current: number; total: number }>("dedupe-progress", (event) => {
+            setVerifyStatus(`Deduplicating ${event.payload.current}/${event.payload.total}: ${event.payload.name}`);
+        });
+        return () => {
+            unlistenProgress.then(f => f());
+            unlistenDedupe.then(f => f());
+            unlistenComplete.then(f => f());
+            unlistenExport.then(f => f());
+            unlistenExportDone.then(f => f());
+            unlistenExportFailed.then(f => f());
+        };
     }, []);
 
-    const handleDelete = async (id: string) => {
+    const handleVerify = async (id: string) => {
         try {
-            await invoke("remove_history_item", { id });
+            const report = await invoke<VerifyReport>("verify_item", { id });
+            alert(describeReport(report));
+        } catch (e) {
+            alert(`Verify failed: ${e}`);
+        }
+    };
+
+    const handleDedupe = async () => {
+        try {
+            const result = await invoke<DedupeReport>("dedupe_library");
+            const skipped = result.files_skipped ? `, ${result.files_skipped} files could not be linked` : "";
+            setVerifyStatus(`Saved ${formatBytes(result.bytes_saved + result.bytes_freed)} by linking ${result.files_linked} duplicate files${skipped}.`);
             fetchHistory();
         } catch (e) {
-            console.error("Failed to delete item:", e);
+            setVerifyStatus(`Dedupe failed: ${e}`);
+        }
+    };
+
+    const handleExport = async (ids: string[]) => {
+        const format = exportFormat;
+        const extension = format === "Zip" ? "zip" : "tar.zst";
+        const outputPath = await save({
+            title: "Export archive",
+            defaultPath: `steamdl-export.${extension}`,
+            filters: [{ name: format === "Zip" ? "Zip archive" : "Zstandard tar archive", extensions: [extension] }],
+        });
+        if (!outputPath) return;
+        try {
+            await invoke("export_items", { ids, outputPath, format });
+        } catch (e) {
+            alert(`Export failed: ${e}`);
+        }
+    };
+
+    const handleVerifyAll = async () => {
+        try {
+            await invoke("verify_library");
+        } catch (e) {
+            console.error("Failed to start verification:", e);
+        }
+    };
+
+    const handleDelete = (id: string) => {
+        setPendingDelete(history.find((h) => h.id === id) ?? null);
+    };
+
+    const confirmDelete = async (keepFiles: boolean) => {
+        if (!pendingDelete) return;
+        const id = pendingDelete.id;
+        setPendingDelete(null);
+        try {
+            await invoke("remove_history_item", { id, keepFiles });
+            fetchHistory();
+        } catch (e) {
+            alert(`Delete failed: ${e}`);
+        }
+    };
+
+    const handleToggleDeploy = async (item: HistoryItem) => {
+        try {
+            if (item.deployments.length > 0) {
+                await invoke("undeploy_item", { id: item.id });
+            } else {
+                await invoke("deploy_item", { id: item.id });
+            }
+            fetchHistory();
+        } catch (e) {
+            console.error("Failed to update deployment:", e);
+            alert(String(e));
+        }
+    };
+
+    const handleRestore = async (id: string) => {
+        try {
+            await invoke("restore_history_item", { id });
+            fetchHistory();
+        } catch (e) {
+            console.error("Failed to restore item:", e);
+        }
+    };
+
+    const handleEmptyTrash = async () => {
+        try {
+            await invoke("empty_trash");
+            fetchHistory();
+        } catch (e) {
+            console.error("Failed to empty trash:", e);
         }
     };
 
@@ -118,18 +417,43 @@ export function HistoryTab() {
             <div className="card-header" style={{ display: "flex", justifyContent: "space-between", alignItems: "center" }}>
                 <div>
                     <h2 className="card-title">History</h2>
-                    <p className="card-description">View past downloads.</p>
+                    <p className="card-description">{verifyStatus ?? "View past downloads."}</p>
+                </div>
+                <div style={{ display: "flex", gap: "8px" }}>
+                    <button className="btn" style={{ border: "1px solid var(--border)" }} onClick={handleVerifyAll}>
+                        Verify All
+                    </button>
+                    <button className="btn" style={{ border: "1px solid var(--border)" }} onClick={handleDedupe} title="Store identical files once and hardlink them into each install">
+                        Dedupe
+                    </button>
+                    <select
+                        className="input"
+                        value={exportFormat}
+                        onChange={(e) => setExportFormat(e.target.value as ArchiveFormat)}
+                        title="Archive format used by Export"
+                        style={{ width: "auto" }}
+                    >
+                        <option value="Zip">.zip</option>
+                        <option value="TarZst">.tar.zst</option>
+                    </select>
+                    <button
+                        className="btn"
+                        style={{ border: "1px solid var(--border)" }}
+                        onClick={() => handleExport(history.filter((h) => !report.missing.includes(h.id) && h.install_path !== "").map((h) => h.id))}
+                    >
+                        Export All
+                    </button>
+                    <button
+                        className="btn"
+                        style={{ backgroundColor: "var(--destructive)", color: "white" }}
+                        onClick={handleClear}
+                    >
+                        Clear All
+                    </button>
                 </div>
-                <button
-                    className="btn"
-                    style={{ backgroundColor: "var(--destructive)", color: "white" }}
-                    onClick={handleClear}
-                >
-                    Clear All
-                </button>
             </div>
             <div style={{ flex: 1, overflowY: "auto" }}>
-                {history.length === 0 ? (
+                {history.length === 0 && report.orphans.length === 0 && trash.length === 0 && tempFolders.length === 0 ? (
                     <div style={{ padding: "2rem", textAlign: "center", color: "var(--muted-foreground)" }}>
                         No history available.
                     </div>
@@ -138,6 +462,7 @@ export function HistoryTab() {
                         <thead style={{ background: "var(--card)", borderBottom: "1px solid var(--border)", position: "sticky", top: 0 }}>
                             <tr>
                                 <th style={{ textAlign: "left", padding: "12px 16px", color: "var(--muted-foreground)" }}>Name</th>
+                                <th style={{ textAlign: "left", padding: "12px 16px", color: "var(--muted-foreground)" }}>Size</th>
                                 <th style={{ textAlign: "left", padding: "12px 16px", color: "var(--muted-foreground)" }}>Date</th>
                                 <th style={{ textAlign: "right", padding: "12px 16px", color: "var(--muted-foreground)" }}>Actions</th>
                             </tr>
@@ -147,14 +472,157 @@ export function HistoryTab() {
                                 <HistoryRow
                                     key={item.id}
                                     item={item}
+                                    exists={!report.missing.includes(item.id) && item.install_path !== ""}
                                     onDelete={handleDelete}
+                                    onToggleDeploy={handleToggleDeploy}
+                                    onVerify={handleVerify}
+                                    onChanged={fetchHistory}
+                                    onExport={handleExport}
                                     onRetry={handleRetry}
                                 />
                             ))}
                         </tbody>
                     </table>
                 )}
+                {report.orphans.length > 0 && (
+                    <div style={{ borderTop: "1px solid var(--border)", padding: "12px 16px" }}>
+                        <div style={{ fontWeight: 500, marginBottom: "8px" }}>Untracked folders</div>
+                        {report.orphans.map((orphan) => (
+                            <div key={orphan.path} style={{ display: "flex", justifyContent: "space-between", alignItems: "center", gap: "8px", padding: "4px 0", fontSize: "0.875rem" }}>
+                                <span title={orphan.path}>
+                                    {orphan.name || orphan.folder_name} · {formatBytes(orphan.size_bytes)}
+                                </span>
+                                <div style={{ display: "flex", gap: "8px" }}>
+                                    <input
+                                        className="input"
+                                        type="text"
+                                        placeholder={orphan.steam_id ?? (orphan.file_id ? `app id:${orphan.file_id}` : "app id:file id")}
+                                        value={adoptIds[orphan.path] ?? ""}
+                                        onChange={(e) => setAdoptIds({ ...adoptIds, [orphan.path]: e.target.value })}
+                                        style={{ height: "2rem", width: "12rem" }}
+                                        title="Steam ID to record, as <app id>:<file id>"
+                                    />
+                                    <button
+                                        className="btn"
+                                        style={{ height: "2rem", padding: "0 0.5rem", background: "transparent", border: "1px solid var(--border)" }}
+                                        onClick={() => handleAdopt(orphan.path)}
+                                        disabled={!orphan.steam_id && !orphan.file_id && !adoptIds[orphan.path]?.trim()}
+                                        title={orphan.steam_id || orphan.file_id || adoptIds[orphan.path]?.trim() ? "Add to history" : "Enter the Steam ID first"}
+                                    >
+                                        ➕ Adopt
+                                    </button>
+                                    <button
+                                        className="btn"
+                                        style={{ height: "2rem", padding: "0 0.5rem", color: "var(--destructive)", background: "transparent", border: "1px solid var(--border)" }}
+                                        onClick={() => handleDeleteOrphan(orphan.path)}
+                                        title="Delete folder"
+                                    >
+                                        🗑️
+                                    </button>
+                                </div>
+                            </div>
+                        ))}
+                    </div>
+                )}
+                {tempFolders.length > 0 && (
+                    <div style={{ borderTop: "1px solid var(--border)", padding: "12px 16px" }}>
+                        <div style={{ display: "flex", justifyContent: "space-between", alignItems: "center", marginBottom: "8px" }}>
+                            <span style={{ fontWeight: 500 }}>
+                                Leftover downloads · {formatBytes(tempFolders.reduce((sum, f) => sum + f.size_bytes, 0))}
+                            </span>
+                            <button
+                                className="btn"
+                                style={{ height: "2rem", padding: "0 0.5rem", color: "var(--destructive)", background: "transparent", border: "1px solid var(--border)" }}
+                                onClick={() => handleCleanTemp(null)}
+                            >
+                                Delete All
+                            </button>
+                        </div>
+                        {tempFolders.map((folder) => (
+                            <div key={folder.path} style={{ display: "flex", justifyContent: "space-between", alignItems: "center", gap: "8px", padding: "4px 0", fontSize: "0.875rem" }}>
+                                <span title={folder.path}>
+                                    {folder.steam_id ?? folder.content_id} · {formatBytes(folder.size_bytes)}
+                                </span>
+                                <div style={{ display: "flex", gap: "8px" }}>
+                                    <button
+                                        className="btn"
+                                        style={{ height: "2rem", padding: "0 0.5rem", background: "transparent", border: "1px solid var(--border)" }}
+                                        onClick={() => handleRequeueTemp(folder)}
+                                        disabled={!folder.steam_id}
+                                        title={folder.steam_id ? "Download again, reusing the partial data" : "Steam ID unknown"}
+                                    >
+                                        🔄 Resume
+                                    </button>
+                                    <button
+                                        className="btn"
+                                        style={{ height: "2rem", padding: "0 0.5rem", color: "var(--destructive)", background: "transparent", border: "1px solid var(--border)" }}
+                                        onClick={() => handleCleanTemp([folder.path])}
+                                        title="Delete folder"
+                                    >
+                                        🗑️
+                                    </button>
+                                </div>
+                            </div>
+                        ))}
+                    </div>
+                )}
+                {trash.length > 0 && (
+                    <div style={{ borderTop: "1px solid var(--border)", padding: "12px 16px" }}>
+                        <div style={{ display: "flex", justifyContent: "space-between", alignItems: "center", marginBottom: "8px" }}>
+                            <span style={{ fontWeight: 500 }}>Trash</span>
+                            <button
+                                className="btn"
+                                style={{ height: "2rem", padding: "0 0.5rem", color: "var(--destructive)", background: "transparent", border: "1px solid var(--border)" }}
+                                onClick={handleEmptyTrash}
+                            >
+                                Empty Trash
+                            </button>
+                        </div>
+                        {trash.map((entry) => (
+                            <div key={entry.id} style={{ display: "flex", justifyContent: "space-between", alignItems: "center", gap: "8px", padding: "4px 0", fontSize: "0.875rem" }}>
+                                <span>
+                                    {entry.item.name} · {formatDate(entry.trashed_at)}{entry.trash_path ? "" : " (files kept)"}
+                                </span>
+                                <button
+                                    className="btn"
+                                    style={{ height: "2rem", padding: "0 0.5rem", background: "transparent", border: "1px solid var(--border)" }}
+                                    onClick={() => handleRestore(entry.id)}
+                                    title="Restore"
+                                >
+                                    ↩️ Restore
+                                </button>
+                            </div>
+                        ))}
+                    </div>
+                )}
             </div>
+            {pendingDelete && (
+                <div style={{ position: "fixed", inset: 0, background: "rgba(0, 0, 0, 0.5)", display: "flex", alignItems: "center", justifyContent: "center", zIndex: 10 }}>
+                    <div className="card" style={{ width: "min(400px, 90vw)" }}>
+                        <div className="card-header">
+                            <h2 className="card-title">Remove {pendingDelete.name}?</h2>
+                            <p className="card-description">
+                                {pendingDelete.install_path
+                                    ? "Remove it from history and keep the files on disk, or move the files to the trash as well."
+                                    : "Remove it from history."}
+                            </p>
+                        </div>
+                        <div className="card-content" style={{ display: "flex", justifyContent: "flex-end", gap: "8px" }}>
+                            <button className="btn" style={{ border: "1px solid var(--border)" }} onClick={() => setPendingDelete(null)} autoFocus>
+                                Cancel
+                            </button>
+                            <button className="btn" style={{ border: "1px solid var(--border)" }} onClick={() => confirmDelete(true)}>
+                                {pendingDelete.install_path ? "Keep files" : "Remove"}
+                            </button>
+                            {pendingDelete.install_path && (
+                                <button className="btn" style={{ color: "var(--destructive)", border: "1px solid var(--border)" }} onClick={() => confirmDelete(false)}>
+                                    Move to trash
+                                </button>
+                            )}
+                        </div>
+                    </div>
+                </div>
+            )}
         </div>
     );
 }
@@ -1,7 +1,8 @@
 import { useEffect, useState } from "react";
 import { invoke } from "@tauri-apps/api/core";
 import { listen } from "@tauri-apps/api/event";
-import { DownloadItem } from "../types";
+import { DownloadItem, EngineState, engineLabel } from "../types";
+import { formatBytes } from "./HistoryTab";
 
 function getLog(status: any) {
     if (typeof status === "object" && status.Failed) return status.Failed;
@@ -24,6 +25,8 @@ function useIsMobile() {
 export function QueueList() {
     const [queue, setQueue] = useState<DownloadItem[]>([]);
     const [expandedId, setExpandedId] = useState<string | null>(null);
+    const [moveProgress, setMoveProgress] = useState<Record<string, number>>({});
+    const [engine, setEngine] = useState<EngineState>("Unprepared");
     const isMobile = useIsMobile();
 
     const fetchQueue = async () => {
@@ -38,7 +41,17 @@ export function QueueList() {
     useEffect(() => {
         fetchQueue();
         const unlisten = listen("queue-update", () => fetchQueue());
-        return () => { unlisten.then(f => f()); };
+        const unlistenMove = listen<{ id: string; copied: number; total: number }>("move-progress", (event) => {
+            const { id, copied, total } = event.payload;
+            setMoveProgress((prev) => ({ ...prev, [id]: total ? Math.floor((copied / total) * 100) : 100 }));
+        });
+        invoke<EngineState>("get_engine_state").then(setEngine).catch(console.error);
+        const unlistenEngine = listen<EngineState>("engine-state", (event) => setEngine(event.payload));
+        return () => {
+            unlisten.then(f => f());
+            unlistenMove.then(f => f());
+            unlistenEngine.then(f => f());
+        };
     }, []);
 
     const toggleLog = (id: string) => {
@@ -114,7 +127,7 @@ export function QueueList() {
                                     >
                                         {item.name}
                                     </div>
-                                    <div style={{ textAlign: "right" }}>{renderStatus(item.status, item.install_path || "", isMobile)}</div>
+                                    <div style={{ textAlign: "right" }}>{renderStatus(item.status, item.install_path || "", isMobile, moveProgress[item.id], engineLabel(engine))}</div>
                                 </div>
 
                                 {isExpanded && log && (
@@ -140,7 +153,7 @@ export function QueueList() {
     );
 }
 
-function renderStatus(status: any, path: string, isMobile: boolean) {
+function renderStatus(status: any, path: string, isMobile: boolean, moved?: number, engine?: string | null) {
     const baseStyle = {
         padding: isMobile ? "2px 6px" : "2px 8px",
         borderRadius: "12px",
@@ -151,9 +164,18 @@ function renderStatus(status: any, path: string, isMobile: boolean) {
         return <span style={{ ...baseStyle, color: "var(--flexoki-yellow)", background: "rgba(173, 131, 1, 0.2)", border: "1px solid rgba(173, 131, 1, 0.3)" }}>Pending</span>;
     
     if (typeof status === "object" && "Downloading" in status) {
-        return <span style={{ ...baseStyle, color: "var(--flexoki-orange)", background: "rgba(188, 82, 21, 0.2)", border: "1px solid rgba(188, 82, 21, 0.3)" }}>Downloading</span>;
+        return <span style={{ ...baseStyle, color: "var(--flexoki-orange)", background: "rgba(188, 82, 21, 0.2)", border: "1px solid rgba(188, 82, 21, 0.3)" }}>{engine || "Downloading"}</span>;
     }
     
+    if (typeof status === "object" && "Moving" in status) {
+        return <span style={{ ...baseStyle, color: "var(--flexoki-orange)", background: "rgba(188, 82, 21, 0.2)", border: "1px solid rgba(188, 82, 21, 0.3)" }}>Moving{moved !== undefined ? ` ${moved}%` : ""}</span>;
+    }
+
+    if (typeof status === "object" && "WaitingForSpace" in status) {
+        const { required, available } = status.WaitingForSpace;
+        return <span title={`Needs ${formatBytes(required)}, ${formatBytes(available)} free`} style={{ ...baseStyle, color: "var(--flexoki-yellow)", background: "rgba(173, 131, 1, 0.2)", border: "1px solid rgba(173, 131, 1, 0.3)" }}>{isMobile ? "No space" : "Waiting for space"}</span>;
+    }
+
     if (status === "Completed")
         return (
             <div style={{ display: "flex", alignItems: "center", justifyContent: "flex-end", gap: isMobile ? "4px" : "8px" }}>
@@ -0,0 +1,159 @@
+import { useEffect, useState } from "react";
+import { invoke } from "@tauri-apps/api/core";
+import { Settings } from "../types";
+
+export function SettingsTab() {
+    const [text, setText] = useState("");
+    const [message, setMessage] = useState<string | null>(null);
+    const [detected, setDetected] = useState<string[] | null>(null);
+    const [account, setAccount] = useState<string | null>(null);
+    const [username, setUsername] = useState("");
+    const [password, setPassword] = useState("");
+
+    const fetchSettings = async () => {
+        try {
+            const settings = await invoke<Settings>("get_settings");
+            setText(JSON.stringify(settings, null, 2));
+        } catch (e) {
+            console.error("Failed to fetch settings:", e);
+        }
+    };
+
+    const fetchAccount = async () => {
+        try {
+            const name = await invoke<string | null>("get_account");
+            setAccount(name);
+            setUsername(name ?? "");
+        } catch (e) {
+            setMessage(`Error: ${e}`);
+        }
+    };
+
+    useEffect(() => {
+        fetchSettings();
+        fetchAccount();
+    }, []);
+
+    const handleSaveAccount = async () => {
+        setMessage(null);
+        try {
+            await invoke("save_account", { username, password: password || null });
+            setPassword("");
+            setMessage(`Saved the Steam account. Set "login" to "Account" to use it for every download; anonymous downloads fall back to it when access is denied.`);
+            fetchAccount();
+        } catch (e) {
+            setMessage(`Error: ${e}`);
+        }
+    };
+
+    const handleRemoveAccount = async () => {
+        if (!confirm("Remove the saved Steam account?")) return;
+        try {
+            await invoke("remove_account");
+            setPassword("");
+            setMessage("Removed the Steam account.");
+            fetchAccount();
+        } catch (e) {
+            setMessage(`Error: ${e}`);
+        }
+    };
+
+    const handleSave = async () => {
+        setMessage(null);
+        try {
+            const settings = JSON.parse(text) as Settings;
+            await invoke("update_settings", { settings });
+            setMessage("Saved.");
+            fetchSettings();
+        } catch (e) {
+            setMessage(`Error: ${e}`);
+        }
+    };
+
+    const handleDetect = async () => {
+        try {
+            setDetected(await invoke<string[]>("detect_steamcmd"));
+        } catch (e) {
+            setMessage(`Error: ${e}`);
+        }
+    };
+
+    const useSteamcmd = (path: string | null) => {
+        try {
+            const settings = JSON.parse(text) as Settings;
+            setText(JSON.stringify({ ...settings, steamcmd_path: path }, null, 2));
+            setMessage(path ? "steamcmd is checked when you save." : null);
+        } catch (e) {
+            setMessage(`Error: ${e}`);
+        }
+    };
+
+    return (
+        <div className="card" style={{ display: "flex", flexDirection: "column", height: "100%", overflow: "hidden" }}>
+            <div className="card-header" style={{ display: "flex", justifyContent: "space-between", alignItems: "center" }}>
+                <div>
+                    <h2 className="card-title">Settings</h2>
+                    <p className="card-description">
+                        Download roots, folder templates such as <code>{"{app_name}/{title} [{file_id}]"}</code> and per-app profiles.
+                    </p>
+                </div>
+                <div style={{ display: "flex", gap: "8px" }}>
+                    <button className="btn" style={{ border: "1px solid var(--border)" }} onClick={handleDetect}>
+                        Find steamcmd
+                    </button>
+                    <button className="btn btn-primary" onClick={handleSave}>
+                        Save
+                    </button>
+                </div>
+            </div>
+            <div className="card-content" style={{ flex: 1, display: "flex", flexDirection: "column", gap: "0.5rem", minHeight: 0 }}>
+                {detected && (
+                    <div style={{ display: "flex", flexDirection: "column", gap: "4px", fontSize: "0.8rem" }}>
+                        {detected.length === 0 && <span style={{ color: "var(--muted-foreground)" }}>No steamcmd found on PATH or in the usual places.</span>}
+                        {detected.map((path) => (
+                            <button key={path} className="btn" style={{ justifyContent: "flex-start", border: "1px solid var(--border)", fontFamily: "monospace" }} onClick={() => useSteamcmd(path)}>
+                                {path}
+                            </button>
+                        ))}
+                        <button className="btn" style={{ justifyContent: "flex-start", border: "1px solid var(--border)" }} onClick={() => useSteamcmd(null)}>
+                            Use the bundled steamcmd
+                        </button>
+                    </div>
+                )}
+                <div style={{ display: "flex", gap: "8px", alignItems: "center", fontSize: "0.8rem" }}>
+                    <span style={{ color: "var(--muted-foreground)", whiteSpace: "nowrap" }}>Steam account</span>
+                    <input
+                        className="input"
+                        placeholder="Account name"
+                        value={username}
+                        onChange={(e) => setUsername(e.target.value)}
+                        spellCheck={false}
+                    />
+                    <input
+                        className="input"
+                        type="password"
+                        placeholder={account ? "Password (unchanged)" : "Password"}
+                        value={password}
+                        onChange={(e) => setPassword(e.target.value)}
+                    />
+                    <button className="btn" style={{ border: "1px solid var(--border)" }} onClick={handleSaveAccount} disabled={!username.trim()}>
+                        Save account
+                    </button>
+                    {account && (
+                        <button className="btn" style={{ border: "1px solid var(--border)" }} onClick={handleRemoveAccount}>
+                            Remove
+                        </button>
+                    )}
+                </div>
+                <textarea
+                    className="input"
+                    style={{ flex: 1, fontFamily: "monospace", fontSize: "0.8rem", resize: "none", height: "auto" }}
+                    value={text}
+                    onChange={(e) => setText(e.target.value)}
+                    spellCheck={false}
+                />
+                {message && <div style={{ fontSize: "0.8rem", color: "var(--muted-foreground)" }}>{message}</div>}
+            </div>
+        </div>
+    );
+}
@@ -0,0 +1,64 @@
+import { useEffect, useState } from "react";
+import { invoke } from "@tauri-apps/api/core";
+import { listen } from "@tauri-apps/api/event";
+import { SteamGuardRequest } from "../types";
+
+export function SteamGuardPrompt() {
+    const [request, setRequest] = useState<SteamGuardRequest | null>(null);
+    const [code, setCode] = useState("");
+    const [error, setError] = useState<string | null>(null);
+
+    useEffect(() => {
+        const unlisten = listen<SteamGuardRequest>("steam-guard-required", (event) => {
+            setRequest(event.payload);
+            setCode("");
+            setError(null);
+        });
+        const unlistenExpired = listen("steam-guard-expired", () => setRequest(null));
+        return () => {
+            unlisten.then(f => f());
+            unlistenExpired.then(f => f());
+        };
+    }, []);
+
+    if (!request) return null;
+
+    const handleSubmit = async () => {
+        try {
+            await invoke("submit_guard_code", { code });
+            setRequest(null);
+        } catch (e) {
+            setError(`${e}`);
+        }
+    };
+
+    return (
+        <div style={{ position: "fixed", inset: 0, background: "rgba(0, 0, 0, 0.5)", display: "flex", alignItems: "center", justifyContent: "center", zIndex: 10 }}>
+            <div className="card" style={{ width: "min(360px, 90vw)" }}>
+                <div className="card-header">
+                    <h2 className="card-title">Steam Guard</h2>
+                    <p className="card-description">
+                        {request.kind === "two-factor"
+                            ? "Enter the code from the Steam mobile app."
+                            : "Enter the code Steam sent to your email."}
+                    </p>
+                </div>
+                <div className="card-content" style={{ display: "flex", flexDirection: "column", gap: "0.5rem" }}>
+                    <input
+                        className="input"
+                        autoFocus
+                        value={code}
+                        onChange={(e) => setCode(e.target.value)}
+                        onKeyDown={(e) => e.key === "Enter" && handleSubmit()}
+                        spellCheck={false}
+                        style={{ fontFamily: "monospace", textTransform: "uppercase" }}
+                    />
+                    {error && <div style={{ fontSize: "0.8rem", color: "var(--flexoki-red-primary)" }}>{error}</div>}
+                    <button className="btn btn-primary" onClick={handleSubmit} disabled={!code.trim()}>
+                        Submit
+                    </button>
+                </div>
+            </div>
+        </div>
+    );
+}
@@ -1,6 +1,8 @@
 export type DownloadStatus =
     | "Pending"
     | { Downloading: { progress: number } }
+    | { WaitingForSpace: { required: number; available: number } }
+    | { Moving: { progress: number } }
     | "Completed"
     | { Failed: string };
 
@@ -11,12 +13,191 @@ export interface DownloadItem {
     status: DownloadStatus;
     install_path?: string;
     created_at: number;
+    app_name?: string | null;
+    destination?: string | null;
+    time_updated?: number | null;
+    file_size?: number | null;
+    flatten?: boolean | null;
+    extract?: ExtractMode | null;
 }
 
+export type ExtractMode = "Off" | "KeepArchive" | "DeleteArchive";
+
+export type FailureClass =
+    | "Engine"
+    | "Login"
+    | "AccessDenied"
+    | "Timeout"
+    | "DiskFull"
+    | "Download"
+    | "FileSystem"
+    | "Collision"
+    | "Unknown";
+
+export interface DownloadFailure {
+    class: FailureClass;
+    message: string;
+}
+
+export type Outcome = "Completed" | { Failed: DownloadFailure };
+
 export interface HistoryItem {
     id: string;
     steam_id: string;
     name: string;
     install_path: string;
     timestamp: number;
+    outcome: Outcome;
+    size_bytes: number;
+    file_count: number;
+    duration_secs: number;
+    avg_speed: number;
+    reported_size?: number | null;
+    deployments: Deployment[];
+    time_updated?: number | null;
+    folder_name?: string;
+    keep_versions?: number | null;
+    extract?: ExtractMode | null;
+    flattened?: string | null;
+}
+
+export interface VersionSnapshot {
+    id: string;
+    created_at: number;
+    item_id: string;
+    time_updated?: number | null;
+    size_bytes: number;
+    file_count: number;
+    flattened?: string | null;
+}
+
+export type DeployMode = "Copy" | "Symlink" | "Hardlink";
+
+export interface Deployment {
+    path: string;
+    mode: DeployMode;
+    deployed_at: number;
+}
+
+export interface DeployTarget {
+    dir: string;
+    mode: DeployMode;
+    auto: boolean;
+}
+
+export interface TempFolder {
+    path: string;
+    content_id: string;
+    steam_id?: string | null;
+    size_bytes: number;
+}
+
+export interface StartupReport {
+    problems: string[];
+    temp_folders: TempFolder[];
+}
+
+export interface OrphanFolder {
+    path: string;
+    folder_name: string;
+    steam_id?: string | null;
+    file_id?: string | null;
+    name?: string | null;
+    size_bytes: number;
+}
+
+export interface LibraryReport {
+    missing: string[];
+    orphans: OrphanFolder[];
+}
+
+export interface TrashEntry {
+    id: string;
+    item: HistoryItem;
+    trash_path?: string | null;
+    trashed_at: number;
+}
+
+export interface AppProfile {
+    root?: string | null;
+    folder_template?: string | null;
+    deploy?: DeployTarget | null;
+    collision_policy?: CollisionPolicy | null;
+    flatten?: boolean | null;
+    keep_versions?: number | null;
+    extract?: ExtractMode | null;
+}
+
+export type CollisionPolicy = "Suffix" | "Overwrite" | "Fail";
+
+export interface Settings {
+    trash_retention_days: number;
+    download_roots: string[];
+    folder_template: string;
+    app_profiles: Record<string, AppProfile>;
+    collision_policy: CollisionPolicy;
+    keep_versions: number;
+    dedupe: boolean;
+    steamcmd_path?: string | null;
+    login: LoginMode;
+}
+
+export type LoginMode = "Anonymous" | "Account";
+
+export interface SteamGuardRequest {
+    id: string;
+    kind: "email" | "two-factor";
+}
+
+export interface DedupeReport {
+    files_scanned: number;
+    files_linked: number;
+    files_skipped: number;
+    bytes_saved: number;
+    bytes_freed: number;
+}
+
+export interface VerifyReport {
+    id: string;
+    missing: string[];
+    modified: string[];
+    extra: string[];
+}
+
+export interface GmaEntry {
+    path: string;
+    size: number;
+    crc: number;
+}
+
+export interface GmaHeader {
+    version: number;
+    steam_id: string;
+    timestamp: number;
+    required_content: string[];
+    name: string;
+    description: string;
+    addon_type?: string | null;
+    tags: string[];
+    author: string;
+    addon_version: number;
+    files: GmaEntry[];
+}
+
+export type ArchiveFormat = "Zip" | "TarZst";
+
+export type EngineState =
+    | "Unprepared"
+    | "Extracting"
+    | { Updating: { progress?: number | null } }
+    | "Ready"
+    | { Failed: string };
+
+export function engineLabel(state: EngineState): string | null {
+    if (state === "Extracting") return "Setting up steamcmd";
+    if (typeof state === "object" && "Updating" in state) {
+        const { progress } = state.Updating;
+        return `Updating steamcmd${progress != null ? ` ${Math.floor(progress)}%` : ""}`;
+    }
+    return null;
 }